@@ -0,0 +1,456 @@
+use crate::error::{Error, Result};
+use crate::literal::Literal;
+use crate::standard::Standard;
+
+// The compiler family whose syntax is used for attributes that standard C lacks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Dialect {
+    #[default]
+    Gnu,
+    Msvc,
+    Standard,
+}
+
+impl std::fmt::Display for Dialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Dialect::Gnu => "GNU C",
+            Dialect::Msvc => "MSVC",
+            Dialect::Standard => "standard C",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Default,
+    Hidden,
+    Protected,
+    Internal,
+}
+
+impl std::fmt::Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Visibility::Default => "default",
+            Visibility::Hidden => "hidden",
+            Visibility::Protected => "protected",
+            Visibility::Internal => "internal",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Attribute {
+    Packed,
+    Aligned(u64),
+    Deprecated(Option<String>),
+    Nodiscard,
+    MaybeUnused,
+    Noreturn,
+    Visibility(Visibility),
+}
+
+impl Attribute {
+    pub fn aligned(alignment: u64) -> Result<Self> {
+        Attribute::Aligned(alignment).validate()
+    }
+
+    pub fn deprecated<S: Into<String>>(message: S) -> Self {
+        Attribute::Deprecated(Some(message.into()))
+    }
+
+    fn validate(self) -> Result<Self> {
+        match self {
+            Attribute::Aligned(alignment) if !alignment.is_power_of_two() => {
+                Err(Error::InvalidAlignment(alignment))
+            }
+            _ => Ok(self),
+        }
+    }
+
+    fn applies_to(&self, subject: Subject) -> bool {
+        match self {
+            Attribute::Packed => subject != Subject::Object && subject != Subject::Function,
+            Attribute::Aligned(_) => subject != Subject::Function,
+            Attribute::Deprecated(_) | Attribute::MaybeUnused => true,
+            Attribute::Nodiscard | Attribute::Noreturn => subject == Subject::Function,
+            Attribute::Visibility(_) => subject == Subject::Object || subject == Subject::Function,
+        }
+    }
+
+    // MSVC can only pack a whole struct, and standard C can only align objects
+    fn is_supported(&self, dialect: Dialect, subject: Subject) -> bool {
+        match (dialect, self) {
+            (Dialect::Gnu, _) => true,
+            (Dialect::Msvc, Attribute::Packed) => subject == Subject::Type,
+            (Dialect::Msvc, Attribute::Aligned(_))
+            | (Dialect::Msvc, Attribute::Deprecated(_))
+            | (Dialect::Msvc, Attribute::Noreturn) => true,
+            (Dialect::Msvc, _) => false,
+            (Dialect::Standard, Attribute::Packed) => false,
+            (Dialect::Standard, Attribute::Aligned(_)) => {
+                subject == Subject::Object || subject == Subject::Member
+            }
+            (Dialect::Standard, Attribute::Visibility(_)) => false,
+            (Dialect::Standard, _) => true,
+        }
+    }
+
+    fn render_gnu(&self) -> String {
+        match self {
+            Attribute::Packed => "packed".to_string(),
+            Attribute::Aligned(alignment) => format!("aligned({})", alignment),
+            Attribute::Deprecated(message) => render_with_message("deprecated", message),
+            Attribute::Nodiscard => "warn_unused_result".to_string(),
+            Attribute::MaybeUnused => "unused".to_string(),
+            Attribute::Noreturn => "noreturn".to_string(),
+            Attribute::Visibility(visibility) => {
+                format!("visibility({})", Literal::String(visibility.to_string()))
+            }
+        }
+    }
+
+    fn render_standard(&self) -> String {
+        match self {
+            Attribute::Nodiscard => "nodiscard".to_string(),
+            Attribute::MaybeUnused => "maybe_unused".to_string(),
+            _ => self.render_gnu(),
+        }
+    }
+}
+
+fn render_with_message(name: &str, message: &Option<String>) -> String {
+    match message {
+        Some(message) => format!("{}({})", name, Literal::String(message.clone())),
+        None => name.to_string(),
+    }
+}
+
+// The GNU spelling, which is also how the attribute is named in errors
+impl std::fmt::Display for Attribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render_gnu())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallingConvention {
+    Cdecl,
+    Stdcall,
+    Fastcall,
+    Vectorcall,
+    MsAbi,
+    SysvAbi,
+}
+
+impl CallingConvention {
+    // The keyword placed just before the declarator, which is the one spot every compiler
+    // accepts, e.g. `int (__stdcall * callback)(void)`
+    pub(crate) fn render(self, dialect: Dialect) -> String {
+        match (dialect, self) {
+            (Dialect::Msvc, CallingConvention::Cdecl) => "__cdecl".to_string(),
+            (Dialect::Msvc, CallingConvention::Stdcall) => "__stdcall".to_string(),
+            (Dialect::Msvc, CallingConvention::Fastcall) => "__fastcall".to_string(),
+            (Dialect::Msvc, CallingConvention::Vectorcall) => "__vectorcall".to_string(),
+            _ => format!("__attribute__(({}))", self),
+        }
+    }
+
+    pub(crate) fn lower(self, dialect: Dialect) -> Result<()> {
+        let supported = match dialect {
+            Dialect::Gnu => true,
+            Dialect::Msvc => !matches!(self, CallingConvention::MsAbi | CallingConvention::SysvAbi),
+            Dialect::Standard => false,
+        };
+        if supported {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedAttribute(self.to_string(), dialect))
+        }
+    }
+}
+
+// The GNU spelling, which is also how the convention is named in errors
+impl std::fmt::Display for CallingConvention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CallingConvention::Cdecl => "cdecl",
+            CallingConvention::Stdcall => "stdcall",
+            CallingConvention::Fastcall => "fastcall",
+            CallingConvention::Vectorcall => "vectorcall",
+            CallingConvention::MsAbi => "ms_abi",
+            CallingConvention::SysvAbi => "sysv_abi",
+        })
+    }
+}
+
+// What the attributes are attached to, which limits what each dialect can express
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Subject {
+    Type,
+    Object,
+    Member,
+    BitField,
+    Function,
+}
+
+impl std::fmt::Display for Subject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Subject::Type => "a type",
+            Subject::Object => "an object",
+            Subject::Member => "a member",
+            Subject::BitField => "a bit-field",
+            Subject::Function => "a function",
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Attributes {
+    attributes: Vec<Attribute>,
+    dialect: Dialect,
+    // Whether standard attributes can use the C23 spellings rather than `_Alignas` and
+    // `_Noreturn`
+    keyword: bool,
+}
+
+impl Attributes {
+    pub(crate) fn push(&mut self, attribute: Attribute, subject: Subject) -> Result<()> {
+        if !attribute.applies_to(subject) {
+            return Err(Error::MisplacedAttribute(format!(
+                "{} on {}",
+                attribute, subject
+            )));
+        }
+        self.attributes.push(attribute.validate()?);
+        Ok(())
+    }
+
+    pub(crate) fn is_packed(&self) -> bool {
+        self.attributes.contains(&Attribute::Packed)
+    }
+
+    // The strictest requested alignment, if any
+    pub(crate) fn alignment(&self) -> Option<u64> {
+        self.attributes
+            .iter()
+            .filter_map(|attribute| match attribute {
+                Attribute::Aligned(alignment) => Some(*alignment),
+                _ => None,
+            })
+            .max()
+    }
+
+    pub(crate) fn lower(
+        &mut self,
+        standard: Standard,
+        dialect: Dialect,
+        subject: Subject,
+    ) -> Result<()> {
+        for attribute in &self.attributes {
+            if !attribute.is_supported(dialect, subject) {
+                return Err(Error::UnsupportedAttribute(
+                    format!("{} on {}", attribute, subject),
+                    dialect,
+                ));
+            }
+            if dialect == Dialect::Standard {
+                match attribute {
+                    Attribute::Aligned(_) => standard.require(Standard::C11, true, "_Alignas")?,
+                    Attribute::Noreturn => standard.require(Standard::C11, true, "_Noreturn")?,
+                    _ => standard.require(Standard::C23, false, "attribute specifiers")?,
+                }
+            }
+        }
+        self.dialect = dialect;
+        self.keyword = standard.at_least(Standard::C23);
+        Ok(())
+    }
+
+    // Standard attribute specifiers, which must come before the whole declaration
+    pub(crate) fn render_leading(&self) -> String {
+        if self.dialect != Dialect::Standard {
+            return String::new();
+        }
+        let list = self
+            .attributes
+            .iter()
+            .filter(|attribute| match attribute {
+                Attribute::Aligned(_) => false,
+                Attribute::Noreturn => self.keyword,
+                _ => true,
+            })
+            .map(|attribute| attribute.render_standard())
+            .collect::<Vec<_>>();
+        if list.is_empty() {
+            String::new()
+        } else {
+            format!("[[{}]] ", list.join(", "))
+        }
+    }
+
+    // The specifiers placed after the storage class of a declaration or member, with a
+    // trailing space
+    pub(crate) fn render_prefix(&self) -> String {
+        match self.dialect {
+            Dialect::Gnu => self.render_gnu(),
+            Dialect::Msvc => self.render_declspec(),
+            Dialect::Standard => self
+                .attributes
+                .iter()
+                .map(|attribute| match attribute {
+                    Attribute::Aligned(alignment) if self.keyword => {
+                        format!("alignas({}) ", alignment)
+                    }
+                    Attribute::Aligned(alignment) => format!("_Alignas({}) ", alignment),
+                    Attribute::Noreturn if !self.keyword => "_Noreturn ".to_string(),
+                    _ => String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    // The specifiers placed after `struct` or `union`, with a trailing space
+    pub(crate) fn render_tag(&self) -> String {
+        match self.dialect {
+            Dialect::Gnu => self.render_gnu(),
+            Dialect::Msvc => self.render_declspec(),
+            Dialect::Standard => self.render_leading(),
+        }
+    }
+
+    // MSVC packs structs with a pragma around the definition, which `__pragma` allows inline
+    pub(crate) fn render_pack(&self) -> Option<(&'static str, &'static str)> {
+        if self.dialect == Dialect::Msvc && self.is_packed() {
+            Some(("__pragma(pack(push, 1)) ", " __pragma(pack(pop))"))
+        } else {
+            None
+        }
+    }
+
+    fn render_gnu(&self) -> String {
+        if self.attributes.is_empty() {
+            return String::new();
+        }
+        let list = self
+            .attributes
+            .iter()
+            .map(|attribute| attribute.render_gnu())
+            .collect::<Vec<_>>();
+        format!("__attribute__(({})) ", list.join(", "))
+    }
+
+    fn render_declspec(&self) -> String {
+        let list = self
+            .attributes
+            .iter()
+            .filter_map(|attribute| match attribute {
+                Attribute::Aligned(alignment) => Some(format!("align({})", alignment)),
+                Attribute::Deprecated(message) => Some(render_with_message("deprecated", message)),
+                Attribute::Noreturn => Some("noreturn".to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if list.is_empty() {
+            String::new()
+        } else {
+            format!("__declspec({}) ", list.join(" "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spellings() {
+        let attributes = |dialect, standard, subject| {
+            let mut attributes = Attributes::default();
+            attributes
+                .push(Attribute::aligned(16).unwrap(), Subject::Object)
+                .unwrap();
+            attributes
+                .lower(standard, dialect, subject)
+                .map(|_| attributes.render_prefix())
+        };
+        assert_eq!(
+            attributes(Dialect::Gnu, Standard::C89, Subject::Type).unwrap(),
+            "__attribute__((aligned(16))) "
+        );
+        assert_eq!(
+            attributes(Dialect::Msvc, Standard::C11, Subject::Object).unwrap(),
+            "__declspec(align(16)) "
+        );
+        assert_eq!(
+            attributes(Dialect::Standard, Standard::C11, Subject::Object).unwrap(),
+            "_Alignas(16) "
+        );
+        assert_eq!(
+            attributes(Dialect::Standard, Standard::C23, Subject::Object).unwrap(),
+            "alignas(16) "
+        );
+        assert!(matches!(
+            attributes(Dialect::Standard, Standard::C23, Subject::Type),
+            Err(Error::UnsupportedAttribute(_, Dialect::Standard))
+        ));
+        assert!(matches!(
+            attributes(Dialect::Standard, Standard::C99, Subject::Object),
+            Err(Error::UnsupportedConstruct(_, Standard::C99))
+        ));
+        assert!(matches!(
+            Attribute::aligned(12),
+            Err(Error::InvalidAlignment(12))
+        ));
+    }
+
+    #[test]
+    fn function_attributes() {
+        let attributes = |dialect, standard| {
+            let mut attributes = Attributes::default();
+            for attribute in [
+                Attribute::deprecated("use bar"),
+                Attribute::Noreturn,
+                Attribute::MaybeUnused,
+            ] {
+                attributes.push(attribute, Subject::Function).unwrap();
+            }
+            attributes
+                .lower(standard, dialect, Subject::Function)
+                .map(|_| {
+                    format!(
+                        "{}{}",
+                        attributes.render_leading(),
+                        attributes.render_prefix()
+                    )
+                })
+        };
+        assert_eq!(
+            attributes(Dialect::Gnu, Standard::C99).unwrap(),
+            "__attribute__((deprecated(\"use bar\"), noreturn, unused)) "
+        );
+        assert_eq!(
+            attributes(Dialect::Standard, Standard::C23).unwrap(),
+            "[[deprecated(\"use bar\"), noreturn, maybe_unused]] "
+        );
+        assert!(matches!(
+            attributes(Dialect::Msvc, Standard::C11),
+            Err(Error::UnsupportedAttribute(_, Dialect::Msvc))
+        ));
+        assert!(matches!(
+            Attributes::default().push(Attribute::Nodiscard, Subject::Object),
+            Err(Error::MisplacedAttribute(_))
+        ));
+        assert_eq!(
+            CallingConvention::Stdcall.render(Dialect::Msvc),
+            "__stdcall"
+        );
+        assert_eq!(
+            CallingConvention::MsAbi.render(Dialect::Gnu),
+            "__attribute__((ms_abi))"
+        );
+        CallingConvention::SysvAbi.lower(Dialect::Msvc).unwrap_err();
+    }
+}
@@ -1,7 +1,15 @@
-use crate::expression::Expression;
+use crate::attribute::{Attribute, Attributes, CallingConvention, Dialect, Subject};
+use crate::error::{Error, Result};
+use crate::expression::{Expression, Precedence};
+use crate::file::Include;
 use crate::identifier::Identifier;
-use crate::r#type::QualifiedType;
+use crate::initializer::Initializer;
+use crate::literal::Literal;
+use crate::r#type::{ParameterList, Type};
+use crate::standard::Standard;
+use crate::statement::{lower_block, render_block, BlockItem};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Storage {
     Auto,
     Static,
@@ -22,39 +30,687 @@ impl std::fmt::Display for Storage {
     }
 }
 
+#[derive(Debug)]
+struct Declarator {
+    r#type: Type,
+    name: Option<Identifier>,
+    initializer: Option<Initializer>,
+}
+
+impl std::fmt::Display for Declarator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.r#type.render_declarator(self.name.as_ref()))?;
+        if let Some(initializer) = &self.initializer {
+            write!(f, " = {}", initializer)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
 pub struct Declaration {
     storage: Option<Storage>,
-    qualified_type: QualifiedType,
-    declaration: DeclarationType,
+    attributes: Attributes,
+    declarators: Vec<Declarator>,
 }
 
-enum DeclarationType {
-    Struct(StructDeclaration),
+impl Declaration {
+    pub fn type_only(r#type: Type) -> Result<Self> {
+        if r#type.has_modifiers() {
+            return Err(Error::MissingDeclarator(r#type.to_string()));
+        }
+        Ok(Self {
+            storage: None,
+            attributes: Attributes::default(),
+            declarators: vec![Declarator {
+                r#type,
+                name: None,
+                initializer: None,
+            }],
+        })
+    }
 }
 
-enum StructType {
-    Struct,
-    Union,
+// The attributes of a typedef belong to the type it names
+fn attribute_subject(storage: &Option<Storage>) -> Subject {
+    if *storage == Some(Storage::Typedef) {
+        Subject::Type
+    } else {
+        Subject::Object
+    }
 }
 
-struct Member {
-    qualified_type: QualifiedType,
-    name: Identifier,
-    width: Option<Expression>,
+impl Declaration {
+    pub(crate) fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        let subject = attribute_subject(&self.storage);
+        self.attributes.lower(standard, dialect, subject)?;
+        for declarator in &mut self.declarators {
+            declarator.r#type.lower(standard, dialect)?;
+            if let Some(initializer) = &mut declarator.initializer {
+                initializer.lower(standard, dialect)?;
+            }
+        }
+        Ok(())
+    }
 }
 
-struct StructDeclaration {
-    name: Option<Identifier>,
-    members: Option<Vec<Member>>,
-    struct_type: StructType,
+impl std::fmt::Display for Declaration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.attributes.render_leading())?;
+        if let Some(storage) = &self.storage {
+            write!(f, "{} ", storage)?;
+        }
+        f.write_str(&self.attributes.render_prefix())?;
+        f.write_str(&self.declarators[0].r#type.render_specifiers())?;
+        for (i, declarator) in self.declarators.iter().enumerate() {
+            let s = declarator.to_string();
+            if i > 0 {
+                write!(f, ", {}", s)?;
+            } else if !s.is_empty() {
+                write!(f, " {}", s)?;
+            }
+        }
+        f.write_str(";")
+    }
+}
+
+#[derive(Debug)]
+pub struct DeclarationBuilder {
+    storage: Option<Storage>,
+    attributes: Vec<Attribute>,
+    declarators: Vec<Declarator>,
+}
+
+impl Default for DeclarationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeclarationBuilder {
+    pub fn new() -> Self {
+        Self {
+            storage: None,
+            attributes: Vec::new(),
+            declarators: Vec::new(),
+        }
+    }
+
+    pub fn storage(mut self, storage: Storage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    // Applies to every declarator, and is checked once the storage class is known
+    pub fn attribute(mut self, attribute: Attribute) -> Self {
+        self.attributes.push(attribute);
+        self
+    }
+
+    pub fn declarator<S: Into<String>>(self, r#type: Type, name: S) -> Result<Self> {
+        self.push(r#type, Identifier::new(name.into())?, None)
+    }
+
+    pub fn declarator_with_initializer<S: Into<String>>(
+        self,
+        r#type: Type,
+        name: S,
+        initializer: Initializer,
+    ) -> Result<Self> {
+        if let Initializer::Designated { .. } = initializer {
+            return Err(Error::MisplacedDesignator(initializer.to_string()));
+        }
+        self.push(r#type, Identifier::new(name.into())?, Some(initializer))
+    }
+
+    pub fn finish(self) -> Result<Declaration> {
+        if self.declarators.is_empty() {
+            return Err(Error::EmptyDeclaration);
+        }
+        if self.storage == Some(Storage::Typedef) {
+            if let Some(declarator) = self.declarators.iter().find(|d| d.initializer.is_some()) {
+                return Err(Error::InitializedTypedef(declarator.to_string()));
+            }
+        }
+        let mut attributes = Attributes::default();
+        for attribute in self.attributes {
+            attributes.push(attribute, attribute_subject(&self.storage))?;
+        }
+        Ok(Declaration {
+            storage: self.storage,
+            attributes,
+            declarators: self.declarators,
+        })
+    }
+
+    fn push(
+        mut self,
+        r#type: Type,
+        name: Identifier,
+        initializer: Option<Initializer>,
+    ) -> Result<Self> {
+        // Every declarator in a declaration shares the same specifiers
+        if let Some(first) = self.declarators.first() {
+            let expected = first.r#type.render_specifiers();
+            let found = r#type.render_specifiers();
+            if expected != found {
+                return Err(Error::MismatchedSpecifiers(expected, found));
+            }
+        }
+        self.declarators.push(Declarator {
+            r#type,
+            name: Some(name),
+            initializer,
+        });
+        Ok(self)
+    }
 }
 
-struct Enumerator {
+#[derive(Debug)]
+pub struct FunctionDeclaration {
+    storage: Option<Storage>,
+    inline: bool,
+    attributes: Attributes,
+    // The macro that marks the function as part of a shared library's interface
+    export: Option<Identifier>,
     name: Identifier,
-    value: Option<Expression>,
+    r#type: Type,
 }
 
-struct EnumDeclaration {
+impl FunctionDeclaration {
+    pub fn define(self, body: Vec<BlockItem>) -> Result<FunctionDefinition> {
+        let unnamed = self
+            .r#type
+            .function_parameters()
+            .is_some_and(|parameters| parameters.iter().any(|p| p.name.is_none()));
+        if unnamed {
+            return Err(Error::UnnamedParameter(self.name.to_string()));
+        }
+        Ok(FunctionDefinition {
+            declaration: self,
+            body,
+        })
+    }
+
+    // Only functions with external linkage can be exported, and inline ones are defined in
+    // every translation unit anyway
+    pub(crate) fn export(&mut self, name: &Identifier) {
+        if self.storage != Some(Storage::Static) && !self.inline {
+            self.export = Some(name.clone());
+        }
+    }
+
+    pub(crate) fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        if self.inline {
+            standard.require(Standard::C99, true, "inline functions")?;
+        }
+        self.attributes
+            .lower(standard, dialect, Subject::Function)?;
+        self.r#type.lower(standard, dialect)
+    }
+
+    fn render_signature(&self) -> String {
+        let mut s = self.attributes.render_leading();
+        if let Some(storage) = &self.storage {
+            s.push_str(&format!("{} ", storage));
+        }
+        if self.inline {
+            s.push_str("inline ");
+        }
+        if let Some(export) = &self.export {
+            s.push_str(&format!("{} ", export));
+        }
+        s.push_str(&self.attributes.render_prefix());
+        s.push_str(&self.r#type.render(Some(&self.name)));
+        s
+    }
+}
+
+impl std::fmt::Display for FunctionDeclaration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{};", self.render_signature())
+    }
+}
+
+#[derive(Debug)]
+pub struct FunctionDefinition {
+    declaration: FunctionDeclaration,
+    body: Vec<BlockItem>,
+}
+
+impl FunctionDefinition {
+    pub(crate) fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        self.declaration.lower(standard, dialect)?;
+        lower_block(&mut self.body, standard, dialect)
+    }
+}
+
+impl std::fmt::Display for FunctionDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.declaration.render_signature())?;
+        f.write_str(&render_block(&self.body, 0))
+    }
+}
+
+#[derive(Debug)]
+pub struct StaticAssert {
+    condition: Expression,
+    message: Option<String>,
+    // Spelled `static_assert`, which is only a keyword from C23
+    keyword: bool,
+}
+
+impl StaticAssert {
+    pub fn new(condition: Expression, message: Option<String>) -> Self {
+        Self {
+            condition,
+            message,
+            keyword: false,
+        }
+    }
+
+    // Headers that must be included for the condition to compile
+    pub fn required_includes(&self) -> Vec<Include> {
+        let mut includes = Vec::new();
+        self.condition.collect_includes(&mut includes);
+        includes
+    }
+
+    pub(crate) fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        standard.require(Standard::C11, true, "static assertions")?;
+        self.keyword = standard.at_least(Standard::C23);
+        self.condition.lower(standard, dialect)
+    }
+}
+
+impl std::fmt::Display for StaticAssert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let condition = self.condition.render_operand(Precedence::Assignment);
+        let keyword = if self.keyword {
+            "static_assert"
+        } else {
+            "_Static_assert"
+        };
+        match &self.message {
+            Some(message) => write!(
+                f,
+                "{}({}, {});",
+                keyword,
+                condition,
+                Literal::String(message.clone())
+            ),
+            None if self.keyword => write!(f, "{}({});", keyword, condition),
+            // The message is only optional from C23, so fall back to the condition itself
+            None => write!(
+                f,
+                "{}({}, {});",
+                keyword,
+                condition,
+                Literal::String(condition.clone())
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FunctionBuilder {
+    storage: Option<Storage>,
+    inline: bool,
+    attributes: Attributes,
     name: Identifier,
-    enumerators: Vec<Enumerator>,
+    return_type: Type,
+    parameters: ParameterList,
+}
+
+impl FunctionBuilder {
+    pub fn new<S: Into<String>>(return_type: Type, name: S) -> Result<Self> {
+        Ok(Self {
+            storage: None,
+            inline: false,
+            attributes: Attributes::default(),
+            name: Identifier::new(name.into())?,
+            return_type,
+            parameters: ParameterList::new(),
+        })
+    }
+
+    pub fn storage(mut self, storage: Storage) -> Result<Self> {
+        match storage {
+            Storage::Static | Storage::Extern => {
+                self.storage = Some(storage);
+                Ok(self)
+            }
+            _ => Err(Error::InvalidStorage(storage)),
+        }
+    }
+
+    pub fn inline(mut self) -> Self {
+        self.inline = true;
+        self
+    }
+
+    pub fn attribute(mut self, attribute: Attribute) -> Result<Self> {
+        self.attributes.push(attribute, Subject::Function)?;
+        Ok(self)
+    }
+
+    pub fn calling_convention(mut self, convention: CallingConvention) -> Self {
+        self.parameters = self.parameters.calling_convention(convention);
+        self
+    }
+
+    pub fn parameter<S: Into<String>>(mut self, r#type: Type, name: S) -> Result<Self> {
+        self.parameters = self.parameters.parameter(r#type, name)?;
+        Ok(self)
+    }
+
+    pub fn unnamed_parameter(mut self, r#type: Type) -> Self {
+        self.parameters = self.parameters.unnamed_parameter(r#type);
+        self
+    }
+
+    pub fn variadic(mut self) -> Self {
+        self.parameters = self.parameters.variadic();
+        self
+    }
+
+    pub fn finish(self) -> Result<FunctionDeclaration> {
+        Ok(FunctionDeclaration {
+            storage: self.storage,
+            inline: self.inline,
+            attributes: self.attributes,
+            export: None,
+            name: self.name,
+            r#type: self.return_type.into_function_returning(self.parameters)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attribute::Visibility;
+    use crate::initializer::Designator;
+    use crate::r#type::TypeBuilder;
+
+    fn int(value: i128) -> Initializer {
+        Initializer::Expression(Expression::Literal(Literal::Signed(value)))
+    }
+
+    #[test]
+    fn static_array() {
+        let d = DeclarationBuilder::new()
+            .storage(Storage::Static)
+            .declarator_with_initializer(
+                TypeBuilder::new()
+                    .sized_array_of(Expression::Literal(Literal::Signed(3)))
+                    .constant()
+                    .int(),
+                "foo",
+                Initializer::List(vec![int(1), int(2), int(3)]),
+            )
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(format!("{}", d), "static const int foo[3] = { 1, 2, 3 };");
+    }
+
+    #[test]
+    fn multiple_declarators() {
+        let d = DeclarationBuilder::new()
+            .storage(Storage::Extern)
+            .declarator(TypeBuilder::new().unsigned_int(), "a")
+            .unwrap()
+            .declarator(TypeBuilder::new().pointer_to().unsigned_int(), "b")
+            .unwrap()
+            .declarator_with_initializer(
+                TypeBuilder::new().array_of().unsigned_int(),
+                "c",
+                Initializer::List(vec![Initializer::List(vec![int(1)])]),
+            )
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(
+            format!("{}", d),
+            "extern unsigned int a, * b, c[] = { { 1 } };"
+        );
+    }
+
+    #[test]
+    fn designated_initializer() {
+        let d = DeclarationBuilder::new()
+            .declarator_with_initializer(
+                TypeBuilder::new().alias_named("config_t").unwrap(),
+                "config",
+                Initializer::List(vec![Initializer::designated(
+                    vec![Designator::field("verbose").unwrap()],
+                    int(1),
+                )
+                .unwrap()]),
+            )
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(format!("{}", d), "config_t config = { .verbose = 1 };");
+
+        DeclarationBuilder::new()
+            .declarator_with_initializer(
+                TypeBuilder::new().int(),
+                "x",
+                Initializer::designated(vec![Designator::field("a").unwrap()], int(1)).unwrap(),
+            )
+            .unwrap_err();
+    }
+
+    #[test]
+    fn mismatched_specifiers() {
+        DeclarationBuilder::new()
+            .declarator(TypeBuilder::new().int(), "a")
+            .unwrap()
+            .declarator(TypeBuilder::new().constant().int(), "b")
+            .unwrap_err();
+    }
+
+    #[test]
+    fn initialized_typedef() {
+        DeclarationBuilder::new()
+            .storage(Storage::Typedef)
+            .declarator_with_initializer(TypeBuilder::new().int(), "foo_t", int(0))
+            .unwrap()
+            .finish()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn type_only() {
+        let d = Declaration::type_only(
+            TypeBuilder::new()
+                .enum_named("foo")
+                .unwrap()
+                .with_enumerators()
+                .enumerator("BAR")
+                .unwrap()
+                .finish(),
+        )
+        .unwrap();
+        assert_eq!(format!("{}", d), "enum foo { BAR };");
+        Declaration::type_only(TypeBuilder::new().pointer_to().int()).unwrap_err();
+    }
+
+    #[test]
+    fn function_prototype() {
+        let d = FunctionBuilder::new(TypeBuilder::new().pointer_to().char(), "foo")
+            .unwrap()
+            .storage(Storage::Extern)
+            .unwrap()
+            .parameter(TypeBuilder::new().int(), "a")
+            .unwrap()
+            .unnamed_parameter(TypeBuilder::new().pointer_to().constant().void())
+            .finish()
+            .unwrap();
+        assert_eq!(format!("{}", d), "extern char * foo(int a, const void *);");
+    }
+
+    #[test]
+    fn variadic_function() {
+        let d = FunctionBuilder::new(TypeBuilder::new().int(), "printf")
+            .unwrap()
+            .parameter(TypeBuilder::new().pointer_to().constant().char(), "format")
+            .unwrap()
+            .variadic()
+            .finish()
+            .unwrap();
+        assert_eq!(format!("{}", d), "int printf(const char * format, ...);");
+    }
+
+    #[test]
+    fn callback_typedef() {
+        let parameters = ParameterList::new()
+            .parameter(TypeBuilder::new().pointer_to().void(), "ctx")
+            .unwrap()
+            .parameter(TypeBuilder::new().pointer_to().constant().char(), "msg")
+            .unwrap();
+        let d = DeclarationBuilder::new()
+            .storage(Storage::Typedef)
+            .declarator(
+                TypeBuilder::new().pointer_to_function_of(parameters).int(),
+                "callback_t",
+            )
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(
+            format!("{}", d),
+            "typedef int (* callback_t)(void * ctx, const char * msg);"
+        );
+    }
+
+    #[test]
+    fn void_parameters() {
+        let d = FunctionBuilder::new(TypeBuilder::new().int(), "foo")
+            .unwrap()
+            .storage(Storage::Static)
+            .unwrap()
+            .inline()
+            .finish()
+            .unwrap();
+        assert_eq!(format!("{}", d), "static inline int foo(void);");
+    }
+
+    #[test]
+    fn returning_array_pointer() {
+        let d = FunctionBuilder::new(
+            TypeBuilder::new()
+                .pointer_to()
+                .sized_array_of(Expression::Literal(Literal::Signed(4)))
+                .double(),
+            "get_row",
+        )
+        .unwrap()
+        .parameter(TypeBuilder::new().int(), "row")
+        .unwrap()
+        .finish()
+        .unwrap();
+        assert_eq!(format!("{}", d), "double (* get_row(int row))[4];");
+    }
+
+    #[test]
+    fn invalid_function() {
+        FunctionBuilder::new(TypeBuilder::new().int(), "foo")
+            .unwrap()
+            .storage(Storage::Register)
+            .unwrap_err();
+        FunctionBuilder::new(TypeBuilder::new().array_of().int(), "foo")
+            .unwrap()
+            .finish()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn function_definition() {
+        let d = FunctionBuilder::new(TypeBuilder::new().void(), "foo")
+            .unwrap()
+            .parameter(TypeBuilder::new().pointer_to().int(), "a")
+            .unwrap()
+            .finish()
+            .unwrap()
+            .define(vec![crate::statement::expression_statement(
+                crate::expression::assign(
+                    crate::expression::dereference(crate::expression::identifier("a").unwrap()),
+                    Expression::Literal(Literal::Signed(0)),
+                ),
+            )
+            .into()])
+            .unwrap();
+        assert_eq!(format!("{}", d), "void foo(int * a)\n{\n    *a = 0;\n}");
+
+        FunctionBuilder::new(TypeBuilder::new().void(), "foo")
+            .unwrap()
+            .unnamed_parameter(TypeBuilder::new().int())
+            .finish()
+            .unwrap()
+            .define(Vec::new())
+            .unwrap_err();
+    }
+
+    #[test]
+    fn function_attributes() {
+        let exit = |standard, dialect| {
+            let mut d = FunctionBuilder::new(TypeBuilder::new().void(), "sdk_exit")
+                .unwrap()
+                .storage(Storage::Extern)
+                .unwrap()
+                .attribute(Attribute::Noreturn)
+                .unwrap()
+                .attribute(Attribute::deprecated("use sdk_shutdown"))
+                .unwrap()
+                .parameter(TypeBuilder::new().int(), "status")
+                .unwrap()
+                .finish()
+                .unwrap();
+            d.lower(standard, dialect).map(|_| d.to_string())
+        };
+        assert_eq!(
+            exit(Standard::C99, Dialect::Gnu).unwrap(),
+            "extern __attribute__((noreturn, deprecated(\"use sdk_shutdown\"))) void sdk_exit(int status);"
+        );
+        assert_eq!(
+            exit(Standard::C11, Dialect::Msvc).unwrap(),
+            "extern __declspec(noreturn deprecated(\"use sdk_shutdown\")) void sdk_exit(int status);"
+        );
+        assert_eq!(
+            exit(Standard::C23, Dialect::Standard).unwrap(),
+            "[[noreturn, deprecated(\"use sdk_shutdown\")]] extern void sdk_exit(int status);"
+        );
+        exit(Standard::C11, Dialect::Standard).unwrap_err();
+
+        let mut callback = FunctionBuilder::new(TypeBuilder::new().int(), "sdk_poll")
+            .unwrap()
+            .attribute(Attribute::Nodiscard)
+            .unwrap()
+            .attribute(Attribute::Visibility(Visibility::Default))
+            .unwrap()
+            .calling_convention(CallingConvention::Stdcall)
+            .finish()
+            .unwrap();
+        assert_eq!(
+            callback.to_string(),
+            "__attribute__((warn_unused_result, visibility(\"default\"))) int __attribute__((stdcall)) sdk_poll(void);"
+        );
+        callback.lower(Standard::C11, Dialect::Msvc).unwrap_err();
+        let declaration = |storage, attribute| {
+            DeclarationBuilder::new()
+                .storage(storage)
+                .attribute(attribute)
+                .declarator(TypeBuilder::new().int(), "handle_t")
+                .unwrap()
+                .finish()
+        };
+        declaration(Storage::Extern, Attribute::Noreturn).unwrap_err();
+        declaration(Storage::Extern, Attribute::Packed).unwrap_err();
+        declaration(Storage::Extern, Attribute::Visibility(Visibility::Hidden)).unwrap();
+        declaration(Storage::Typedef, Attribute::Visibility(Visibility::Hidden)).unwrap_err();
+        declaration(Storage::Typedef, Attribute::aligned(8).unwrap()).unwrap();
+    }
 }
@@ -1,12 +1,130 @@
+use crate::attribute::Dialect;
+use crate::declaration::Storage;
+use crate::standard::Standard;
+
 #[derive(Debug)]
 pub enum Error {
     BadIdentifier(String),
+    KeywordIdentifier(String, Standard),
+    ReservedIdentifier(String),
+    EmptyDeclaration,
+    MissingDeclarator(String),
+    MismatchedSpecifiers(String, String),
+    InitializedTypedef(String),
+    InvalidStorage(Storage),
+    InvalidReturnType(String),
+    InvalidElementType(String),
+    InvalidAtomicType(String),
+    MisplacedFlexibleArray(String),
+    UnnamedParameter(String),
+    ObjectLikeMacroParameter(String),
+    DuplicateParameter(String),
+    MisplacedDesignator(String),
+    MissingDesignator(String),
+    UnrepresentableCharacter(char),
+    IntegerOutOfRange(String),
+    InvalidFloat(String),
+    InvalidBitIntWidth(u32),
+    UnsupportedConstruct(String, Standard),
+    IncompleteType(String),
+    TypeTooLarge(String),
+    UnnamedType(String),
+    NonConstantExpression(String),
+    InvalidAlignment(u64),
+    MisplacedAttribute(String),
+    UnsupportedAttribute(String, Dialect),
+    MissingStandard(Dialect),
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::BadIdentifier(identifier) => write!(f, "invalid identifier: {}", identifier),
+            Error::KeywordIdentifier(identifier, standard) => {
+                write!(f, "identifier is a keyword in {}: {}", standard, identifier)
+            }
+            Error::ReservedIdentifier(identifier) => {
+                write!(
+                    f,
+                    "identifier is reserved for the implementation: {}",
+                    identifier
+                )
+            }
+            Error::EmptyDeclaration => f.write_str("declaration has no declarators"),
+            Error::MissingDeclarator(r#type) => {
+                write!(f, "declaration of type {} requires a declarator", r#type)
+            }
+            Error::MismatchedSpecifiers(expected, found) => write!(
+                f,
+                "declarators must share specifiers: expected {}, found {}",
+                expected, found
+            ),
+            Error::InitializedTypedef(declarator) => {
+                write!(f, "typedef cannot have an initializer: {}", declarator)
+            }
+            Error::InvalidStorage(storage) => write!(f, "invalid storage class: {}", storage),
+            Error::InvalidReturnType(r#type) => write!(f, "invalid return type: {}", r#type),
+            Error::InvalidElementType(r#type) => {
+                write!(f, "invalid array element type: {}", r#type)
+            }
+            Error::InvalidAtomicType(r#type) => write!(f, "invalid atomic type: {}", r#type),
+            Error::MisplacedFlexibleArray(field) => write!(
+                f,
+                "flexible array member must be the last of several struct members: {}",
+                field
+            ),
+            Error::UnnamedParameter(function) => {
+                write!(f, "function definition has unnamed parameter: {}", function)
+            }
+            Error::ObjectLikeMacroParameter(name) => {
+                write!(f, "object-like macro cannot have parameters: {}", name)
+            }
+            Error::DuplicateParameter(name) => write!(f, "duplicate parameter: {}", name),
+            Error::UnrepresentableCharacter(value) => write!(
+                f,
+                "character does not fit in a single code unit: U+{:04X}",
+                *value as u32
+            ),
+            Error::IntegerOutOfRange(value) => {
+                write!(f, "integer constant out of range: {}", value)
+            }
+            Error::InvalidFloat(value) => write!(f, "invalid floating constant: {}", value),
+            Error::InvalidBitIntWidth(width) => write!(f, "invalid _BitInt width: {}", width),
+            Error::UnsupportedConstruct(construct, standard) => {
+                write!(f, "{} is not supported in {}", construct, standard)
+            }
+            Error::IncompleteType(the_type) => write!(f, "incomplete type: {}", the_type),
+            Error::TypeTooLarge(the_type) => write!(f, "type is too large: {}", the_type),
+            Error::UnnamedType(the_type) => write!(f, "type has no tag: {}", the_type),
+            Error::NonConstantExpression(expression) => {
+                write!(f, "not an integer constant expression: {}", expression)
+            }
+            Error::InvalidAlignment(alignment) => {
+                write!(f, "alignment is not a power of two: {}", alignment)
+            }
+            Error::MisplacedAttribute(attribute) => {
+                write!(f, "attribute cannot be applied here: {}", attribute)
+            }
+            Error::UnsupportedAttribute(attribute, dialect) => {
+                write!(f, "{} is not supported in {}", attribute, dialect)
+            }
+            Error::MissingStandard(dialect) => {
+                write!(f, "{} attributes need a target standard", dialect)
+            }
+            Error::MisplacedDesignator(initializer) => {
+                write!(
+                    f,
+                    "designator outside of an initializer list: {}",
+                    initializer
+                )
+            }
+            Error::MissingDesignator(initializer) => {
+                write!(
+                    f,
+                    "designated initializer has no designators: {}",
+                    initializer
+                )
+            }
         }
     }
 }
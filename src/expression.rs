@@ -1,13 +1,32 @@
+use crate::attribute::Dialect;
 use crate::error::Result;
+use crate::file::Include;
 use crate::identifier::Identifier;
-use crate::literal::Literal;
+use crate::initializer::Initializer;
+use crate::literal::{DataModel, FloatConstant, IntegerConstant, IntegerSuffix, Literal};
 use crate::r#type::Type;
+use crate::standard::Standard;
 
 #[derive(Debug)]
 pub enum Expression {
     Identifier(Identifier),
     Literal(Literal),
     Sizeof(Type),
+    Alignof(Type),
+    UnderscoreAlignof(Type),
+    Defined(Identifier),
+    Offsetof {
+        r#type: Box<Type>,
+        member: Identifier,
+    },
+    Call {
+        function: Box<Expression>,
+        arguments: Vec<Expression>,
+    },
+    CompoundLiteral {
+        r#type: Box<Type>,
+        initializer: Box<Initializer>,
+    },
     Unary {
         expression: Box<Expression>,
         operation: UnaryOperation,
@@ -24,80 +43,291 @@ pub enum Expression {
     },
 }
 
+// C operator precedence, from loosest to tightest binding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Precedence {
+    Comma,
+    Assignment,
+    Conditional,
+    LogicalOr,
+    LogicalAnd,
+    BitwiseOr,
+    BitwiseXor,
+    BitwiseAnd,
+    Equality,
+    Relational,
+    Shift,
+    Additive,
+    Multiplicative,
+    Unary,
+    Postfix,
+    Primary,
+}
+
+impl Expression {
+    fn precedence(&self) -> Precedence {
+        match self {
+            // Negative constants are really a unary minus applied to a literal
+            Expression::Literal(val) if val.to_string().starts_with('-') => Precedence::Unary,
+            Expression::Identifier(_) | Expression::Literal(_) => Precedence::Primary,
+            Expression::Sizeof(_)
+            | Expression::Alignof(_)
+            | Expression::UnderscoreAlignof(_)
+            | Expression::Defined(_) => Precedence::Unary,
+            Expression::Call { .. }
+            | Expression::CompoundLiteral { .. }
+            | Expression::Offsetof { .. } => Precedence::Postfix,
+            Expression::Unary { operation, .. } => operation.precedence(),
+            Expression::Binary { operation, .. } => operation.precedence(),
+            Expression::Ternary { .. } => Precedence::Conditional,
+        }
+    }
+
+    // Render this expression as an operand that must bind at least as tightly as `min`
+    pub(crate) fn render_operand(&self, min: Precedence) -> String {
+        if self.precedence() < min {
+            format!("({})", self)
+        } else {
+            self.to_string()
+        }
+    }
+
+    // Rewrite or reject constructs that the target standard lacks
+    pub(crate) fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        match self {
+            Expression::Identifier(_) | Expression::Defined(_) => Ok(()),
+            Expression::Offsetof { r#type, .. } => r#type.lower(standard, dialect),
+            Expression::Literal(value) => value.lower(standard),
+            Expression::Sizeof(the_type) => the_type.lower(standard, dialect),
+            Expression::Alignof(the_type) if !standard.at_least(Standard::C23) => {
+                standard.require(Standard::C11, true, "alignof")?;
+                // Without <stdalign.h>, only the keyword spelling is available before C23
+                let placeholder = Expression::Literal(Literal::Signed(0));
+                if let Expression::Alignof(the_type) = std::mem::replace(self, placeholder) {
+                    *self = Expression::UnderscoreAlignof(the_type);
+                }
+                self.lower(standard, dialect)
+            }
+            Expression::Alignof(the_type) => the_type.lower(standard, dialect),
+            Expression::UnderscoreAlignof(the_type) => {
+                standard.require(Standard::C11, true, "_Alignof")?;
+                the_type.lower(standard, dialect)
+            }
+            Expression::Call {
+                function,
+                arguments,
+            } => {
+                function.lower(standard, dialect)?;
+                arguments
+                    .iter_mut()
+                    .try_for_each(|argument| argument.lower(standard, dialect))
+            }
+            Expression::CompoundLiteral {
+                r#type,
+                initializer,
+            } => {
+                standard.require(Standard::C99, true, "compound literals")?;
+                r#type.lower(standard, dialect)?;
+                initializer.lower(standard, dialect)
+            }
+            Expression::Unary {
+                expression,
+                operation,
+            } => {
+                match operation {
+                    UnaryOperation::Cast(the_type) => the_type.lower(standard, dialect)?,
+                    UnaryOperation::ArrayAccess(index) => index.lower(standard, dialect)?,
+                    _ => {}
+                }
+                expression.lower(standard, dialect)
+            }
+            Expression::Binary { left, right, .. } => {
+                left.lower(standard, dialect)?;
+                right.lower(standard, dialect)
+            }
+            Expression::Ternary {
+                condition,
+                if_true,
+                if_false,
+            } => {
+                condition.lower(standard, dialect)?;
+                if_true.lower(standard, dialect)?;
+                if_false.lower(standard, dialect)
+            }
+        }
+    }
+
+    // Headers needed by the expression, such as <stddef.h> for `offsetof`
+    pub(crate) fn collect_includes(&self, includes: &mut Vec<Include>) {
+        match self {
+            Expression::Identifier(_) | Expression::Literal(_) | Expression::Defined(_) => {}
+            Expression::Offsetof { r#type, .. } => {
+                let include = Include::bracket("stddef.h");
+                if !includes.contains(&include) {
+                    includes.push(include);
+                }
+                r#type.collect_includes(includes);
+            }
+            Expression::Sizeof(the_type)
+            | Expression::Alignof(the_type)
+            | Expression::UnderscoreAlignof(the_type) => the_type.collect_includes(includes),
+            Expression::CompoundLiteral { r#type, .. } => r#type.collect_includes(includes),
+            Expression::Call {
+                function,
+                arguments,
+            } => {
+                function.collect_includes(includes);
+                for argument in arguments {
+                    argument.collect_includes(includes);
+                }
+            }
+            Expression::Unary {
+                expression,
+                operation,
+            } => {
+                match operation {
+                    UnaryOperation::Cast(the_type) => the_type.collect_includes(includes),
+                    UnaryOperation::ArrayAccess(index) => index.collect_includes(includes),
+                    _ => {}
+                }
+                expression.collect_includes(includes);
+            }
+            Expression::Binary { left, right, .. } => {
+                left.collect_includes(includes);
+                right.collect_includes(includes);
+            }
+            Expression::Ternary {
+                condition,
+                if_true,
+                if_false,
+            } => {
+                condition.collect_includes(includes);
+                if_true.collect_includes(includes);
+                if_false.collect_includes(includes);
+            }
+        }
+    }
+
+    fn render_prefix(symbol: &str, expression: &Expression) -> String {
+        let operand = expression.render_operand(Precedence::Unary);
+        // Avoid gluing tokens together, such as `- -x` becoming `--x`
+        if symbol.ends_with(|c| operand.starts_with(c) && "+-&".contains(c)) {
+            format!("{}({})", symbol, expression)
+        } else {
+            format!("{}{}", symbol, operand)
+        }
+    }
+}
+
 impl std::fmt::Display for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Expression::Identifier(val) => write!(f, "{}", val),
             Expression::Literal(val) => write!(f, "{}", val),
             Expression::Sizeof(val) => write!(f, "sizeof({})", val),
+            Expression::Alignof(val) => write!(f, "alignof({})", val),
+            Expression::UnderscoreAlignof(val) => write!(f, "_Alignof({})", val),
+            Expression::Defined(val) => write!(f, "defined({})", val),
+            Expression::Offsetof { r#type, member } => {
+                write!(f, "offsetof({}, {})", r#type, member)
+            }
+            Expression::Call {
+                function,
+                arguments,
+            } => write!(
+                f,
+                "{}({})",
+                function.render_operand(Precedence::Postfix),
+                arguments
+                    .iter()
+                    .map(|x| x.render_operand(Precedence::Assignment))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Expression::CompoundLiteral {
+                r#type,
+                initializer,
+            } => match initializer.as_ref() {
+                Initializer::List(_) => write!(f, "({}){}", r#type, initializer),
+                _ => write!(f, "({}){{ {} }}", r#type, initializer),
+            },
             Expression::Unary {
                 expression,
                 operation,
-            } => match operation {
-                UnaryOperation::PrefixIncrement => write!(f, "++({})", expression),
-                UnaryOperation::PrefixDecrement => write!(f, "--({})", expression),
-                UnaryOperation::PostfixIncrement => write!(f, "({})++", expression),
-                UnaryOperation::PostfixDecrement => write!(f, "({})--", expression),
-                UnaryOperation::Address => write!(f, "&({})", expression),
-                UnaryOperation::Dereference => write!(f, "*({})", expression),
-                UnaryOperation::Plus => write!(f, "+({})", expression),
-                UnaryOperation::Minus => write!(f, "-({})", expression),
-                UnaryOperation::BitwiseNegate => write!(f, "~({})", expression),
-                UnaryOperation::LogicalNegate => write!(f, "!({})", expression),
-                UnaryOperation::Sizeof => write!(f, "sizeof({})", expression),
-                UnaryOperation::Cast(casted) => write!(f, "({})({})", casted, expression),
-                UnaryOperation::ArrayAccess(index) => {
-                    write!(f, "({})[{}]", expression, index.as_ref())
-                }
-                UnaryOperation::StructAccess(field) => write!(f, "({}).{}", expression, field),
-                UnaryOperation::StructDereference(field) => {
-                    write!(f, "({})->{}", expression, field)
+            } => {
+                let postfix = || expression.render_operand(Precedence::Postfix);
+                match operation {
+                    UnaryOperation::PrefixIncrement => {
+                        f.write_str(&Self::render_prefix("++", expression))
+                    }
+                    UnaryOperation::PrefixDecrement => {
+                        f.write_str(&Self::render_prefix("--", expression))
+                    }
+                    UnaryOperation::PostfixIncrement => write!(f, "{}++", postfix()),
+                    UnaryOperation::PostfixDecrement => write!(f, "{}--", postfix()),
+                    UnaryOperation::Address => f.write_str(&Self::render_prefix("&", expression)),
+                    UnaryOperation::Dereference => {
+                        f.write_str(&Self::render_prefix("*", expression))
+                    }
+                    UnaryOperation::Plus => f.write_str(&Self::render_prefix("+", expression)),
+                    UnaryOperation::Minus => f.write_str(&Self::render_prefix("-", expression)),
+                    UnaryOperation::BitwiseNegate => {
+                        f.write_str(&Self::render_prefix("~", expression))
+                    }
+                    UnaryOperation::LogicalNegate => {
+                        f.write_str(&Self::render_prefix("!", expression))
+                    }
+                    UnaryOperation::Sizeof => write!(f, "sizeof({})", expression),
+                    UnaryOperation::Cast(casted) => write!(
+                        f,
+                        "({}){}",
+                        casted,
+                        expression.render_operand(Precedence::Unary)
+                    ),
+                    UnaryOperation::ArrayAccess(index) => write!(f, "{}[{}]", postfix(), index),
+                    UnaryOperation::StructAccess(field) => write!(f, "{}.{}", postfix(), field),
+                    UnaryOperation::StructDereference(field) => {
+                        write!(f, "{}->{}", postfix(), field)
+                    }
                 }
-            },
+            }
             Expression::Binary {
                 left,
                 right,
                 operation,
             } => {
-                let symbol = match operation {
-                    BinaryOperation::Add => "+",
-                    BinaryOperation::Subtract => "-",
-                    BinaryOperation::Multiply => "*",
-                    BinaryOperation::Divide => "/",
-                    BinaryOperation::Modulo => "%",
-                    BinaryOperation::LeftShift => "<<",
-                    BinaryOperation::RightShift => ">>",
-                    BinaryOperation::LessThan => "<",
-                    BinaryOperation::GreaterThan => ">",
-                    BinaryOperation::LessThanEqual => "<=",
-                    BinaryOperation::GreaterThanEqual => ">=",
-                    BinaryOperation::Equal => "==",
-                    BinaryOperation::NotEqual => "!=",
-                    BinaryOperation::BitwiseAnd => "&",
-                    BinaryOperation::BitwiseOr => "|",
-                    BinaryOperation::BitwiseXor => "^",
-                    BinaryOperation::LogicalAnd => "&&",
-                    BinaryOperation::LogicalOr => "||",
-                    BinaryOperation::Comma => ",",
-                    BinaryOperation::Assign => "=",
-                    BinaryOperation::AddAssign => "+=",
-                    BinaryOperation::SubtractAssign => "-=",
-                    BinaryOperation::MultiplyAssign => "*=",
-                    BinaryOperation::DivideAssign => "/=",
-                    BinaryOperation::ModuloAssign => "%=",
-                    BinaryOperation::LeftShiftAssign => "<<=",
-                    BinaryOperation::RightShiftAssign => ">>=",
-                    BinaryOperation::BitwiseAndAssign => "&=",
-                    BinaryOperation::BitwiseOrAssign => "|=",
-                    BinaryOperation::BitwiseXorAssign => "^=",
+                let precedence = operation.precedence();
+                let (left, right) = if precedence == Precedence::Assignment {
+                    // Assignment is right-associative and requires a unary expression on the left
+                    (
+                        left.render_operand(Precedence::Unary),
+                        right.render_operand(Precedence::Assignment),
+                    )
+                } else {
+                    let right = if right.precedence() <= precedence {
+                        format!("({})", right)
+                    } else {
+                        right.to_string()
+                    };
+                    (left.render_operand(precedence), right)
                 };
-                write!(f, "{} {} {}", left, symbol, right)
+                if let BinaryOperation::Comma = operation {
+                    write!(f, "{}, {}", left, right)
+                } else {
+                    write!(f, "{} {} {}", left, operation.symbol(), right)
+                }
             }
             Expression::Ternary {
                 condition,
                 if_true,
                 if_false,
-            } => write!(f, "({}) ? ({}) : ({})", condition, if_true, if_false),
+            } => write!(
+                f,
+                "{} ? {} : {}",
+                condition.render_operand(Precedence::LogicalOr),
+                if_true,
+                if_false.render_operand(Precedence::Conditional)
+            ),
         }
     }
 }
@@ -155,6 +385,78 @@ pub enum BinaryOperation {
     BitwiseXorAssign,
 }
 
+impl UnaryOperation {
+    fn precedence(&self) -> Precedence {
+        match self {
+            UnaryOperation::PostfixIncrement
+            | UnaryOperation::PostfixDecrement
+            | UnaryOperation::ArrayAccess(_)
+            | UnaryOperation::StructAccess(_)
+            | UnaryOperation::StructDereference(_) => Precedence::Postfix,
+            _ => Precedence::Unary,
+        }
+    }
+}
+
+impl BinaryOperation {
+    fn precedence(&self) -> Precedence {
+        match self {
+            BinaryOperation::Multiply | BinaryOperation::Divide | BinaryOperation::Modulo => {
+                Precedence::Multiplicative
+            }
+            BinaryOperation::Add | BinaryOperation::Subtract => Precedence::Additive,
+            BinaryOperation::LeftShift | BinaryOperation::RightShift => Precedence::Shift,
+            BinaryOperation::LessThan
+            | BinaryOperation::GreaterThan
+            | BinaryOperation::LessThanEqual
+            | BinaryOperation::GreaterThanEqual => Precedence::Relational,
+            BinaryOperation::Equal | BinaryOperation::NotEqual => Precedence::Equality,
+            BinaryOperation::BitwiseAnd => Precedence::BitwiseAnd,
+            BinaryOperation::BitwiseXor => Precedence::BitwiseXor,
+            BinaryOperation::BitwiseOr => Precedence::BitwiseOr,
+            BinaryOperation::LogicalAnd => Precedence::LogicalAnd,
+            BinaryOperation::LogicalOr => Precedence::LogicalOr,
+            BinaryOperation::Comma => Precedence::Comma,
+            _ => Precedence::Assignment,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOperation::Add => "+",
+            BinaryOperation::Subtract => "-",
+            BinaryOperation::Multiply => "*",
+            BinaryOperation::Divide => "/",
+            BinaryOperation::Modulo => "%",
+            BinaryOperation::LeftShift => "<<",
+            BinaryOperation::RightShift => ">>",
+            BinaryOperation::LessThan => "<",
+            BinaryOperation::GreaterThan => ">",
+            BinaryOperation::LessThanEqual => "<=",
+            BinaryOperation::GreaterThanEqual => ">=",
+            BinaryOperation::Equal => "==",
+            BinaryOperation::NotEqual => "!=",
+            BinaryOperation::BitwiseAnd => "&",
+            BinaryOperation::BitwiseOr => "|",
+            BinaryOperation::BitwiseXor => "^",
+            BinaryOperation::LogicalAnd => "&&",
+            BinaryOperation::LogicalOr => "||",
+            BinaryOperation::Comma => ",",
+            BinaryOperation::Assign => "=",
+            BinaryOperation::AddAssign => "+=",
+            BinaryOperation::SubtractAssign => "-=",
+            BinaryOperation::MultiplyAssign => "*=",
+            BinaryOperation::DivideAssign => "/=",
+            BinaryOperation::ModuloAssign => "%=",
+            BinaryOperation::LeftShiftAssign => "<<=",
+            BinaryOperation::RightShiftAssign => ">>=",
+            BinaryOperation::BitwiseAndAssign => "&=",
+            BinaryOperation::BitwiseOrAssign => "|=",
+            BinaryOperation::BitwiseXorAssign => "^=",
+        }
+    }
+}
+
 pub fn identifier<S: Into<String>>(name: S) -> Result<Expression> {
     Ok(Expression::Identifier(Identifier::new(name.into())?))
 }
@@ -163,10 +465,143 @@ pub fn literal(value: Literal) -> Expression {
     Expression::Literal(value)
 }
 
+// C has no negative integer constants, so negative values are built from a unary minus.
+// The most negative value of a type is written as `-MAX - 1` to keep it in that type.
+pub fn signed_integer(value: i128, model: DataModel) -> Result<Expression> {
+    if value >= 0 {
+        return Ok(literal(Literal::Integer(IntegerConstant::signed(
+            value as u128,
+            model,
+        )?)));
+    }
+    let magnitude = value.unsigned_abs();
+    let below = IntegerConstant::signed(magnitude - 1, model)?;
+    match IntegerConstant::signed(magnitude, model) {
+        Ok(constant) if constant.suffix() == below.suffix() => {
+            Ok(minus(literal(Literal::Integer(constant))))
+        }
+        _ => Ok(subtract(
+            minus(literal(Literal::Integer(below))),
+            literal(Literal::Integer(IntegerConstant::new(
+                1,
+                IntegerSuffix::None,
+            ))),
+        )),
+    }
+}
+
+// How infinities and NaNs, which have no constant syntax, are spelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonFinite {
+    // `INFINITY` and `NAN`, which require `<math.h>`
+    MathMacros,
+    // `1.0 / 0.0` and `0.0 / 0.0`, which need no header
+    Division,
+}
+
+// Exact double value, along with the header its spelling requires, if any
+pub fn double_value(value: f64, non_finite: NonFinite) -> (Expression, Option<Include>) {
+    let constant = |v: f64| literal(Literal::Double(FloatConstant::from_f64(v).unwrap()));
+    real_value(
+        value,
+        value.is_nan(),
+        value.is_sign_negative(),
+        non_finite,
+        || constant(value.abs()),
+        constant,
+    )
+}
+
+// Exact float value, along with the header its spelling requires, if any
+pub fn float_value(value: f32, non_finite: NonFinite) -> (Expression, Option<Include>) {
+    let constant = |v: f32| literal(Literal::Float(FloatConstant::from_f32(v).unwrap()));
+    real_value(
+        value as f64,
+        value.is_nan(),
+        value.is_sign_negative(),
+        non_finite,
+        || constant(value.abs()),
+        |v| constant(v as f32),
+    )
+}
+
+fn real_value(
+    value: f64,
+    nan: bool,
+    negative: bool,
+    non_finite: NonFinite,
+    magnitude: impl FnOnce() -> Expression,
+    constant: impl Fn(f64) -> Expression,
+) -> (Expression, Option<Include>) {
+    if value.is_finite() {
+        // As with integers, negative values are built from a unary minus
+        let expression = magnitude();
+        return if negative {
+            (minus(expression), None)
+        } else {
+            (expression, None)
+        };
+    }
+    let expression = match non_finite {
+        NonFinite::MathMacros if nan => Expression::Identifier(Identifier::new("NAN").unwrap()),
+        NonFinite::MathMacros => Expression::Identifier(Identifier::new("INFINITY").unwrap()),
+        NonFinite::Division if nan => divide(constant(0.0), constant(0.0)),
+        NonFinite::Division => divide(constant(1.0), constant(0.0)),
+    };
+    let expression = if negative && !nan {
+        minus(expression)
+    } else {
+        expression
+    };
+    match non_finite {
+        NonFinite::MathMacros => (expression, Some(Include::bracket("math.h"))),
+        NonFinite::Division => (expression, None),
+    }
+}
+
 pub fn sizeof_type(the_type: Type) -> Expression {
     Expression::Sizeof(the_type)
 }
 
+pub fn alignof_type(the_type: Type) -> Expression {
+    Expression::Alignof(the_type)
+}
+
+pub fn underscore_alignof_type(the_type: Type) -> Expression {
+    Expression::UnderscoreAlignof(the_type)
+}
+
+// The `offsetof` macro from <stddef.h>
+pub fn offsetof<S: Into<String>>(the_type: Type, member: S) -> Result<Expression> {
+    Ok(Expression::Offsetof {
+        r#type: Box::new(the_type),
+        member: Identifier::new(member.into())?,
+    })
+}
+
+pub fn call(function: Expression, arguments: Vec<Expression>) -> Expression {
+    Expression::Call {
+        function: Box::new(function),
+        arguments,
+    }
+}
+
+pub fn compound_literal(the_type: Type, initializer: Initializer) -> Expression {
+    Expression::CompoundLiteral {
+        r#type: Box::new(the_type),
+        initializer: Box::new(initializer),
+    }
+}
+
+// Join expressions with the comma operator, evaluated left to right
+pub fn comma_list(expressions: Vec<Expression>) -> Option<Expression> {
+    expressions.into_iter().reduce(comma)
+}
+
+pub fn defined<S: Into<String>>(name: S) -> Result<Expression> {
+    Ok(Expression::Defined(Identifier::macro_name(name)?))
+}
+
 pub fn ternary(condition: Expression, if_true: Expression, if_false: Expression) -> Expression {
     Expression::Ternary {
         condition: Box::new(condition),
@@ -268,3 +703,173 @@ binary_gen!(right_shift_assign, RightShiftAssign);
 binary_gen!(bitwsie_and_assign, BitwiseAndAssign);
 binary_gen!(bitwise_or_assign, BitwiseOrAssign);
 binary_gen!(bitwise_xor_assign, BitwiseXorAssign);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(name: &str) -> Expression {
+        identifier(name).unwrap()
+    }
+
+    #[test]
+    fn binary_precedence() {
+        let e = multiply(add(id("a"), id("b")), id("c"));
+        assert_eq!(format!("{}", e), "(a + b) * c");
+        let e = add(id("a"), multiply(id("b"), id("c")));
+        assert_eq!(format!("{}", e), "a + b * c");
+        let e = subtract(id("a"), subtract(id("b"), id("c")));
+        assert_eq!(format!("{}", e), "a - (b - c)");
+        let e = subtract(subtract(id("a"), id("b")), id("c"));
+        assert_eq!(format!("{}", e), "a - b - c");
+        let e = equal(bitwise_and(id("flags"), id("mask")), id("value"));
+        assert_eq!(format!("{}", e), "(flags & mask) == value");
+    }
+
+    #[test]
+    fn assignment_associativity() {
+        let e = assign(id("a"), assign(id("b"), id("c")));
+        assert_eq!(format!("{}", e), "a = b = c");
+        let e = add_assign(dereference(id("p")), comma(id("x"), id("y")));
+        assert_eq!(format!("{}", e), "*p += (x, y)");
+        let e = assign(ternary(id("c"), id("a"), id("b")), id("d"));
+        assert_eq!(format!("{}", e), "(c ? a : b) = d");
+    }
+
+    #[test]
+    fn unary_and_postfix() {
+        let e = struct_access(dereference(id("p")), "x").unwrap();
+        assert_eq!(format!("{}", e), "(*p).x");
+        let e = dereference(postfix_inc(id("p")));
+        assert_eq!(format!("{}", e), "*p++");
+        let e = array_access(
+            struct_dereference(id("s"), "items").unwrap(),
+            add(id("i"), id("j")),
+        );
+        assert_eq!(format!("{}", e), "s->items[i + j]");
+        let e = minus(minus(id("x")));
+        assert_eq!(format!("{}", e), "-(-x)");
+        let e = minus(literal(Literal::Signed(-5)));
+        assert_eq!(format!("{}", e), "-(-5)");
+        let e = cast(
+            add(id("a"), id("b")),
+            crate::r#type::TypeBuilder::new().long(),
+        );
+        assert_eq!(format!("{}", e), "(long)(a + b)");
+    }
+
+    #[test]
+    fn calls() {
+        let e = call(
+            id("foo"),
+            vec![id("a"), comma(id("b"), id("c")), add(id("d"), id("e"))],
+        );
+        assert_eq!(format!("{}", e), "foo(a, (b, c), d + e)");
+        let e = call(dereference(id("callback")), Vec::new());
+        assert_eq!(format!("{}", e), "(*callback)()");
+        let e = call(
+            struct_dereference(id("ops"), "read").unwrap(),
+            vec![id("ctx")],
+        );
+        assert_eq!(format!("{}", e), "ops->read(ctx)");
+    }
+
+    #[test]
+    fn compound_literals() {
+        use crate::r#type::TypeBuilder;
+        let point = || TypeBuilder::new().alias_named("point_t").unwrap();
+        let e = compound_literal(
+            point(),
+            Initializer::List(vec![
+                Initializer::Expression(literal(Literal::Signed(1))),
+                Initializer::Expression(literal(Literal::Signed(2))),
+            ]),
+        );
+        assert_eq!(format!("{}", e), "(point_t){ 1, 2 }");
+        let e = struct_access(
+            compound_literal(point(), Initializer::Expression(id("x"))),
+            "y",
+        )
+        .unwrap();
+        assert_eq!(format!("{}", e), "(point_t){ x }.y");
+        let e = compound_literal(
+            point(),
+            Initializer::designated(
+                vec![crate::initializer::Designator::field("x").unwrap()],
+                Initializer::expression(literal(Literal::Signed(1))),
+            )
+            .unwrap(),
+        );
+        assert_eq!(format!("{}", e), "(point_t){ .x = 1 }");
+    }
+
+    #[test]
+    fn alignof_and_comma_list() {
+        use crate::r#type::TypeBuilder;
+        assert_eq!(
+            format!("{}", alignof_type(TypeBuilder::new().double())),
+            "alignof(double)"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                underscore_alignof_type(TypeBuilder::new().pointer_to().void())
+            ),
+            "_Alignof(void *)"
+        );
+        let e = comma_list(vec![id("a"), id("b"), id("c")]).unwrap();
+        assert_eq!(format!("{}", e), "a, b, c");
+        assert!(comma_list(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn signed_integers() {
+        let e = signed_integer(-5, DataModel::LP64).unwrap();
+        assert_eq!(format!("{}", e), "-5");
+        assert_eq!(format!("{}", minus(e)), "-(-5)");
+        let e = signed_integer(i32::MIN as i128, DataModel::LP64).unwrap();
+        assert_eq!(format!("{}", e), "-2147483647 - 1");
+        let e = signed_integer(i32::MIN as i128 - 1, DataModel::LP64).unwrap();
+        assert_eq!(format!("{}", e), "-2147483649L");
+        let e = signed_integer(i64::MIN as i128, DataModel::LLP64).unwrap();
+        assert_eq!(format!("{}", e), "-9223372036854775807LL - 1");
+        signed_integer(i128::MAX, DataModel::LP64).unwrap_err();
+        signed_integer(i64::MIN as i128 - 1, DataModel::LP64).unwrap_err();
+    }
+
+    #[test]
+    fn ternary_nesting() {
+        let e = ternary(
+            logical_or(id("a"), id("b")),
+            ternary(id("c"), id("d"), id("e")),
+            ternary(id("f"), id("g"), id("h")),
+        );
+        assert_eq!(format!("{}", e), "a || b ? c ? d : e : f ? g : h");
+        let e = ternary(ternary(id("a"), id("b"), id("c")), id("d"), id("e"));
+        assert_eq!(format!("{}", e), "(a ? b : c) ? d : e");
+    }
+
+    #[test]
+    fn real_values() {
+        let (e, include) = double_value(-0.1, NonFinite::MathMacros);
+        assert_eq!(format!("{}", e), "-0.1");
+        assert!(include.is_none());
+        let (e, _) = float_value(16777216.0, NonFinite::Division);
+        assert_eq!(format!("{}", e), "16777216.0f");
+
+        let (e, include) = double_value(f64::NEG_INFINITY, NonFinite::MathMacros);
+        assert_eq!(format!("{}", e), "-INFINITY");
+        assert_eq!(format!("{}", include.unwrap()), "#include <math.h>");
+        let (e, _) = double_value(f64::NAN, NonFinite::MathMacros);
+        assert_eq!(format!("{}", e), "NAN");
+
+        let (e, include) = double_value(f64::INFINITY, NonFinite::Division);
+        assert_eq!(format!("{}", e), "1.0 / 0.0");
+        assert!(include.is_none());
+        let (e, _) = float_value(f32::NAN, NonFinite::Division);
+        assert_eq!(
+            format!("{}", multiply(identifier("x").unwrap(), e)),
+            "x * (0.0f / 0.0f)"
+        );
+    }
+}
@@ -1,16 +1,41 @@
-use crate::declaration::Declaration;
+use crate::attribute::Dialect;
+use crate::declaration::{Declaration, FunctionDeclaration, FunctionDefinition, StaticAssert};
+use crate::error::{Error, Result};
+use crate::expression::{defined, logical_or, Expression};
 use crate::identifier::Identifier;
+use crate::preprocessor::{Directive, Macro, MacroBuilder};
+use crate::standard::Standard;
+use std::path::Path;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IncludeMethod {
     Quote,
     Bracket,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Include {
     method: IncludeMethod,
     path: String,
 }
 
+impl Include {
+    pub fn new<S: Into<String>>(method: IncludeMethod, path: S) -> Self {
+        Self {
+            method,
+            path: path.into(),
+        }
+    }
+
+    pub fn quote<S: Into<String>>(path: S) -> Self {
+        Self::new(IncludeMethod::Quote, path)
+    }
+
+    pub fn bracket<S: Into<String>>(path: S) -> Self {
+        Self::new(IncludeMethod::Bracket, path)
+    }
+}
+
 impl std::fmt::Display for Include {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let (quote1, quote2) = match &self.method {
@@ -27,44 +52,956 @@ impl std::fmt::Display for Include {
     }
 }
 
+#[derive(Debug)]
+pub enum Item {
+    Declaration(Declaration),
+    FunctionDeclaration(FunctionDeclaration),
+    FunctionDefinition(FunctionDefinition),
+    Directive(Directive),
+    Conditional(Conditional),
+    StaticAssert(StaticAssert),
+}
+
+impl Item {
+    fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        match self {
+            Item::Declaration(declaration) => declaration.lower(standard, dialect),
+            Item::FunctionDeclaration(declaration) => declaration.lower(standard, dialect),
+            Item::FunctionDefinition(definition) => definition.lower(standard, dialect),
+            Item::Directive(directive) => directive.lower(standard, dialect),
+            Item::Conditional(conditional) => conditional.lower(standard, dialect),
+            Item::StaticAssert(assertion) => assertion.lower(standard, dialect),
+        }
+    }
+}
+
+fn lower_items(items: &mut [Item], standard: Standard, dialect: Dialect) -> Result<()> {
+    items
+        .iter_mut()
+        .try_for_each(|item| item.lower(standard, dialect))
+}
+
+// Attributes are only respelled while lowering, so a dialect needs a standard to go with it
+fn lower_file(
+    items: &mut [Item],
+    standard: Option<Standard>,
+    dialect: Option<Dialect>,
+) -> Result<()> {
+    match (standard, dialect) {
+        (Some(standard), dialect) => lower_items(items, standard, dialect.unwrap_or_default()),
+        (None, Some(dialect)) => Err(Error::MissingStandard(dialect)),
+        (None, None) => Ok(()),
+    }
+}
+
+fn export_items(items: &mut [Item], name: &Identifier) {
+    for item in items {
+        match item {
+            Item::FunctionDeclaration(declaration) => declaration.export(name),
+            Item::Conditional(conditional) => {
+                export_items(&mut conditional.items, name);
+                for (_, items) in &mut conditional.elifs {
+                    export_items(items, name);
+                }
+                if let Some(items) = &mut conditional.otherwise {
+                    export_items(items, name);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Define `name` to export symbols from a Windows DLL while `building` is defined and to import
+// them otherwise, or to give them default visibility with GCC and Clang
+fn export_macro(name: &Identifier, building: &Identifier) -> Result<Conditional> {
+    let define = |tokens: &str| -> Result<Macro> {
+        Ok(MacroBuilder::object_like(name.to_string())?
+            .tokens(tokens)
+            .finish())
+    };
+    let windows = ConditionalBuilder::ifdef(building.to_string())?
+        .item(define("__declspec(dllexport)")?)
+        .otherwise()
+        .item(define("__declspec(dllimport)")?)
+        .finish();
+    Ok(
+        ConditionalBuilder::new(logical_or(defined("_WIN32")?, defined("__CYGWIN__")?))
+            .item(windows)
+            .elif(defined("__GNUC__")?)
+            .item(define("__attribute__((visibility(\"default\")))")?)
+            .otherwise()
+            .item(define("")?)
+            .finish(),
+    )
+}
+
+impl std::fmt::Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Item::Declaration(declaration) => write!(f, "{}", declaration),
+            Item::FunctionDeclaration(declaration) => write!(f, "{}", declaration),
+            Item::FunctionDefinition(definition) => write!(f, "{}", definition),
+            Item::Directive(directive) => write!(f, "{}", directive),
+            Item::Conditional(conditional) => write!(f, "{}", conditional),
+            Item::StaticAssert(assertion) => write!(f, "{}", assertion),
+        }
+    }
+}
+
+impl From<Declaration> for Item {
+    fn from(declaration: Declaration) -> Self {
+        Item::Declaration(declaration)
+    }
+}
+
+impl From<FunctionDeclaration> for Item {
+    fn from(declaration: FunctionDeclaration) -> Self {
+        Item::FunctionDeclaration(declaration)
+    }
+}
+
+impl From<FunctionDefinition> for Item {
+    fn from(definition: FunctionDefinition) -> Self {
+        Item::FunctionDefinition(definition)
+    }
+}
+
+impl From<Directive> for Item {
+    fn from(directive: Directive) -> Self {
+        Item::Directive(directive)
+    }
+}
+
+impl From<Macro> for Item {
+    fn from(definition: Macro) -> Self {
+        Item::Directive(Directive::Define(definition))
+    }
+}
+
+impl From<Conditional> for Item {
+    fn from(conditional: Conditional) -> Self {
+        Item::Conditional(conditional)
+    }
+}
+
+impl From<StaticAssert> for Item {
+    fn from(assertion: StaticAssert) -> Self {
+        Item::StaticAssert(assertion)
+    }
+}
+
+#[derive(Debug)]
+pub enum Condition {
+    If(Expression),
+    Ifdef(Identifier),
+    Ifndef(Identifier),
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Condition::If(expression) => write!(f, "#if {}", expression),
+            Condition::Ifdef(name) => write!(f, "#ifdef {}", name),
+            Condition::Ifndef(name) => write!(f, "#ifndef {}", name),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Conditional {
+    condition: Condition,
+    items: Vec<Item>,
+    elifs: Vec<(Expression, Vec<Item>)>,
+    otherwise: Option<Vec<Item>>,
+}
+
+impl Conditional {
+    fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        if let Condition::If(condition) = &mut self.condition {
+            condition.lower(standard, dialect)?;
+        }
+        lower_items(&mut self.items, standard, dialect)?;
+        for (condition, items) in &mut self.elifs {
+            condition.lower(standard, dialect)?;
+            lower_items(items, standard, dialect)?;
+        }
+        match &mut self.otherwise {
+            Some(items) => lower_items(items, standard, dialect),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Conditional {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.condition)?;
+        for item in &self.items {
+            writeln!(f, "{}", item)?;
+        }
+        for (condition, items) in &self.elifs {
+            writeln!(f, "#elif {}", condition)?;
+            for item in items {
+                writeln!(f, "{}", item)?;
+            }
+        }
+        if let Some(items) = &self.otherwise {
+            writeln!(f, "#else")?;
+            for item in items {
+                writeln!(f, "{}", item)?;
+            }
+        }
+        f.write_str("#endif")
+    }
+}
+
+#[derive(Debug)]
+pub struct ConditionalBuilder {
+    conditional: Conditional,
+}
+
+impl ConditionalBuilder {
+    pub fn new(condition: Expression) -> Self {
+        Self::with_condition(Condition::If(condition))
+    }
+
+    pub fn ifdef<S: Into<String>>(name: S) -> Result<Self> {
+        Ok(Self::with_condition(Condition::Ifdef(
+            Identifier::macro_name(name)?,
+        )))
+    }
+
+    pub fn ifndef<S: Into<String>>(name: S) -> Result<Self> {
+        Ok(Self::with_condition(Condition::Ifndef(
+            Identifier::macro_name(name)?,
+        )))
+    }
+
+    fn with_condition(condition: Condition) -> Self {
+        Self {
+            conditional: Conditional {
+                condition,
+                items: Vec::new(),
+                elifs: Vec::new(),
+                otherwise: None,
+            },
+        }
+    }
+
+    pub fn item<I: Into<Item>>(mut self, item: I) -> Self {
+        match self.conditional.elifs.last_mut() {
+            Some((_, items)) => items.push(item.into()),
+            None => self.conditional.items.push(item.into()),
+        }
+        self
+    }
+
+    pub fn elif(mut self, condition: Expression) -> Self {
+        self.conditional.elifs.push((condition, Vec::new()));
+        self
+    }
+
+    pub fn otherwise(mut self) -> ConditionalElseBuilder {
+        self.conditional.otherwise = Some(Vec::new());
+        ConditionalElseBuilder {
+            conditional: self.conditional,
+        }
+    }
+
+    pub fn finish(self) -> Conditional {
+        self.conditional
+    }
+}
+
+#[derive(Debug)]
+pub struct ConditionalElseBuilder {
+    conditional: Conditional,
+}
+
+impl ConditionalElseBuilder {
+    pub fn item<I: Into<Item>>(mut self, item: I) -> Self {
+        if let Some(items) = &mut self.conditional.otherwise {
+            items.push(item.into());
+        }
+        self
+    }
+
+    pub fn finish(self) -> Conditional {
+        self.conditional
+    }
+}
+
+// Only touch the file if the contents differ, to preserve modification times
+fn write_if_changed(path: &Path, contents: &str) -> std::io::Result<bool> {
+    if let Ok(existing) = std::fs::read(path) {
+        if existing == contents.as_bytes() {
+            return Ok(false);
+        }
+    }
+    std::fs::write(path, contents)?;
+    Ok(true)
+}
+
+fn write_body(
+    f: &mut std::fmt::Formatter<'_>,
+    includes: &[Include],
+    items: &[Item],
+    extern_c: bool,
+) -> std::fmt::Result {
+    if !includes.is_empty() {
+        for include in includes {
+            writeln!(f, "{}", include)?;
+        }
+        writeln!(f)?;
+    }
+    if extern_c {
+        writeln!(f, "#ifdef __cplusplus\nextern \"C\" {{\n#endif\n")?;
+    }
+    for item in items {
+        writeln!(f, "{}", item)?;
+    }
+    if extern_c {
+        writeln!(f, "\n#ifdef __cplusplus\n}}\n#endif")?;
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
 pub struct Header {
     guard: Option<Identifier>,
+    pragma_once: bool,
+    extern_c: bool,
+    standard: Option<Standard>,
     includes: Vec<Include>,
-    declarations: Vec<Declaration>,
+    items: Vec<Item>,
+}
+
+impl Header {
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> std::io::Result<bool> {
+        write_if_changed(path.as_ref(), &self.to_string())
+    }
 }
 
 impl std::fmt::Display for Header {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(guard) = self.guard {
+        if self.pragma_once {
+            writeln!(f, "#pragma once")?;
+        }
+        if let Some(guard) = &self.guard {
             writeln!(f, "#ifndef {}", guard)?;
             writeln!(f, "#define {}", guard)?;
         }
-        for include in self.includes {
-            writeln!(f, "{}", include)?;
+        let empty = self.includes.is_empty() && self.items.is_empty() && !self.extern_c;
+        if (self.pragma_once || self.guard.is_some()) && !empty {
+            writeln!(f)?;
         }
-        for declaration in self.declarations {
-            writeln!(f, "{}", declaration)?;
-        }
-        if let Some(guard) = self.guard {
-            writeln!(f, "#endif // {}", guard)?;
+        write_body(f, &self.includes, &self.items, self.extern_c)?;
+        if let Some(guard) = &self.guard {
+            if !self.items.is_empty() || self.extern_c {
+                writeln!(f)?;
+            }
+            // Line comments are only available from C99
+            match self.standard {
+                Some(standard) if !standard.at_least(Standard::C99) && !standard.is_gnu() => {
+                    writeln!(f, "#endif /* {} */", guard)?
+                }
+                _ => writeln!(f, "#endif // {}", guard)?,
+            }
         }
         Ok(())
     }
 }
 
+#[derive(Debug)]
+pub struct HeaderBuilder {
+    guard: Option<Identifier>,
+    pragma_once: bool,
+    extern_c: bool,
+    standard: Option<Standard>,
+    dialect: Option<Dialect>,
+    // The export macro and the macro defined while building the library
+    export: Option<(Identifier, Identifier)>,
+    includes: Vec<Include>,
+    items: Vec<Item>,
+}
+
+impl Default for HeaderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeaderBuilder {
+    pub fn new() -> Self {
+        Self {
+            guard: None,
+            pragma_once: false,
+            extern_c: false,
+            standard: None,
+            dialect: None,
+            export: None,
+            includes: Vec::new(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn guard<S: Into<String>>(mut self, name: S) -> Result<Self> {
+        self.guard = Some(Identifier::new(name.into())?);
+        Ok(self)
+    }
+
+    // Derive a guard such as `FOO_BAR_H` from a path like `include/foo-bar.h`. Runs of other
+    // characters become a single `_`, non-ASCII ones are spelled out as `U00E9` so that `é.h`
+    // and `h.h` differ, and `_foo.h` and `2d.h` give `FOO_H` and `N2D_H` rather than reserved or
+    // invalid names
+    pub fn guard_from_file_name<P: AsRef<Path>>(self, path: P) -> Result<Self> {
+        let file_name = path
+            .as_ref()
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if !file_name.chars().any(|c| c.is_ascii_alphanumeric()) {
+            return Err(Error::BadIdentifier(file_name));
+        }
+        let mut guard = String::new();
+        for c in file_name.chars() {
+            if c.is_ascii_alphanumeric() {
+                guard.push(c.to_ascii_uppercase());
+                continue;
+            }
+            if !guard.is_empty() && !guard.ends_with('_') {
+                guard.push('_');
+            }
+            if !c.is_ascii() {
+                guard.push_str(&format!("U{:04X}", u32::from(c)));
+            }
+        }
+        let guard = guard.trim_end_matches('_');
+        if guard.starts_with(|c: char| c.is_ascii_digit()) {
+            self.guard(format!("N{}", guard))
+        } else {
+            self.guard(guard)
+        }
+    }
+
+    pub fn pragma_once(mut self) -> Self {
+        self.pragma_once = true;
+        self
+    }
+
+    // Wrap the items in `extern "C"` when compiled as C++
+    pub fn extern_c(mut self) -> Self {
+        self.extern_c = true;
+        self
+    }
+
+    // Define an export macro such as `MYLIB_API` for a shared library, and mark every extern
+    // function declaration with it
+    pub fn export_macro<S: Into<String>, T: Into<String>>(
+        mut self,
+        name: S,
+        building: T,
+    ) -> Result<Self> {
+        self.export = Some((
+            Identifier::new(name.into())?,
+            Identifier::new(building.into())?,
+        ));
+        Ok(self)
+    }
+
+    // Lower the items to the given standard, failing on constructs it can't express
+    pub fn standard(mut self, standard: Standard) -> Self {
+        self.standard = Some(standard);
+        self
+    }
+
+    // Spell attributes for the given compiler, which defaults to GNU C
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = Some(dialect);
+        self
+    }
+
+    pub fn include(mut self, include: Include) -> Self {
+        self.includes.push(include);
+        self
+    }
+
+    pub fn item<I: Into<Item>>(mut self, item: I) -> Self {
+        self.items.push(item.into());
+        self
+    }
+
+    pub fn finish(mut self) -> Result<Header> {
+        if let Some((name, building)) = &self.export {
+            export_items(&mut self.items, name);
+            self.items.insert(0, export_macro(name, building)?.into());
+        }
+        lower_file(&mut self.items, self.standard, self.dialect)?;
+        Ok(Header {
+            guard: self.guard,
+            pragma_once: self.pragma_once,
+            extern_c: self.extern_c,
+            standard: self.standard,
+            includes: self.includes,
+            items: self.items,
+        })
+    }
+}
+
+#[derive(Debug)]
 pub struct TranslationUnit {
     includes: Vec<Include>,
-    declarations: Vec<Declaration>,
+    items: Vec<Item>,
+}
+
+impl TranslationUnit {
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> std::io::Result<bool> {
+        write_if_changed(path.as_ref(), &self.to_string())
+    }
 }
 
 impl std::fmt::Display for TranslationUnit {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for include in self.includes {
-            writeln!(f, "{}", include)?;
-        }
-        for declaration in self.declarations {
-            writeln!(f, "{}", declaration)?;
+        write_body(f, &self.includes, &self.items, false)
+    }
+}
+
+#[derive(Debug)]
+pub struct TranslationUnitBuilder {
+    standard: Option<Standard>,
+    dialect: Option<Dialect>,
+    includes: Vec<Include>,
+    items: Vec<Item>,
+}
+
+impl Default for TranslationUnitBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranslationUnitBuilder {
+    pub fn new() -> Self {
+        Self {
+            standard: None,
+            dialect: None,
+            includes: Vec::new(),
+            items: Vec::new(),
         }
-        Ok(())
+    }
+
+    // Lower the items to the given standard, failing on constructs it can't express
+    pub fn standard(mut self, standard: Standard) -> Self {
+        self.standard = Some(standard);
+        self
+    }
+
+    // Spell attributes for the given compiler, which defaults to GNU C
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = Some(dialect);
+        self
+    }
+
+    pub fn include(mut self, include: Include) -> Self {
+        self.includes.push(include);
+        self
+    }
+
+    pub fn item<I: Into<Item>>(mut self, item: I) -> Self {
+        self.items.push(item.into());
+        self
+    }
+
+    pub fn finish(mut self) -> Result<TranslationUnit> {
+        lower_file(&mut self.items, self.standard, self.dialect)?;
+        Ok(TranslationUnit {
+            includes: self.includes,
+            items: self.items,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attribute::Attribute;
+    use crate::declaration::{DeclarationBuilder, FunctionBuilder, Storage};
+    use crate::expression::{alignof_type, equal, literal, sizeof_type};
+    use crate::initializer::{Designator, Initializer};
+    use crate::literal::{IntegerConstant, IntegerSuffix, Literal, Radix};
+    use crate::r#type::TypeBuilder;
+    use crate::statement::{r#return, BlockItem};
+
+    fn prototype() -> FunctionDeclaration {
+        FunctionBuilder::new(TypeBuilder::new().int(), "foo")
+            .unwrap()
+            .finish()
+            .unwrap()
+    }
+
+    #[test]
+    fn header() {
+        let h = HeaderBuilder::new()
+            .guard_from_file_name("include/my-lib.h")
+            .unwrap()
+            .include(Include::bracket("stdint.h"))
+            .include(Include::quote("config.h"))
+            .item(
+                DeclarationBuilder::new()
+                    .storage(Storage::Extern)
+                    .declarator(TypeBuilder::new().int(), "counter")
+                    .unwrap()
+                    .finish()
+                    .unwrap(),
+            )
+            .item(
+                crate::preprocessor::MacroBuilder::object_like("MY_LIB_VERSION")
+                    .unwrap()
+                    .tokens("2")
+                    .finish(),
+            )
+            .item(prototype())
+            .finish()
+            .unwrap();
+        assert_eq!(
+            format!("{}", h),
+            "#ifndef MY_LIB_H\n#define MY_LIB_H\n\n#include <stdint.h>\n#include \"config.h\"\n\nextern int counter;\n#define MY_LIB_VERSION 2\nint foo(void);\n\n#endif // MY_LIB_H\n"
+        );
+    }
+
+    #[test]
+    fn guard_from_file_name() {
+        let guard = |path| {
+            let h = HeaderBuilder::new()
+                .guard_from_file_name(path)
+                .unwrap()
+                .finish()
+                .unwrap();
+            format!("{}", h).lines().next().unwrap().to_string()
+        };
+        assert_eq!(guard("include/foo-bar.h"), "#ifndef FOO_BAR_H");
+        assert_eq!(guard("_private.h"), "#ifndef PRIVATE_H");
+        assert_eq!(guard("2d.h"), "#ifndef N2D_H");
+        assert_eq!(guard("__init__.h"), "#ifndef INIT_H");
+        assert_eq!(guard("é.h"), "#ifndef U00E9_H");
+        assert_eq!(guard("h.h"), "#ifndef H_H");
+        assert!(HeaderBuilder::new()
+            .guard_from_file_name("include/..")
+            .is_err());
+        assert!(HeaderBuilder::new().guard_from_file_name("é").is_err());
+    }
+
+    #[test]
+    fn pragma_once() {
+        let h = HeaderBuilder::new()
+            .pragma_once()
+            .item(prototype())
+            .finish()
+            .unwrap();
+        assert_eq!(format!("{}", h), "#pragma once\n\nint foo(void);\n");
+    }
+
+    #[test]
+    fn translation_unit() {
+        let t = TranslationUnitBuilder::new()
+            .include(Include::quote("foo.h"))
+            .item(prototype().define(Vec::new()).unwrap())
+            .finish()
+            .unwrap();
+        assert_eq!(
+            format!("{}", t),
+            "#include \"foo.h\"\n\nint foo(void)\n{\n}\n"
+        );
+    }
+
+    #[test]
+    fn write_to() {
+        let path = std::env::temp_dir().join(format!("crusty-write-to-{}.h", std::process::id()));
+        let h = HeaderBuilder::new().pragma_once().finish().unwrap();
+        assert!(h.write_to(&path).unwrap());
+        assert!(!h.write_to(&path).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "#pragma once\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn conditional() {
+        use crate::expression::{defined, logical_or};
+        let c = ConditionalBuilder::new(defined("_WIN32").unwrap())
+            .item(Directive::undef("min").unwrap())
+            .elif(logical_or(
+                defined("__linux__").unwrap(),
+                defined("__APPLE__").unwrap(),
+            ))
+            .item(
+                ConditionalBuilder::ifndef("NDEBUG")
+                    .unwrap()
+                    .item(prototype())
+                    .finish(),
+            )
+            .otherwise()
+            .item(Directive::undef("max").unwrap())
+            .finish();
+        assert_eq!(
+            format!("{}", c),
+            "#if defined(_WIN32)\n#undef min\n#elif defined(__linux__) || defined(__APPLE__)\n#ifndef NDEBUG\nint foo(void);\n#endif\n#else\n#undef max\n#endif"
+        );
+    }
+
+    #[test]
+    fn extern_c() {
+        let h = HeaderBuilder::new()
+            .guard("FOO_H")
+            .unwrap()
+            .extern_c()
+            .item(prototype())
+            .finish()
+            .unwrap();
+        assert_eq!(
+            format!("{}", h),
+            "#ifndef FOO_H\n#define FOO_H\n\n#ifdef __cplusplus\nextern \"C\" {\n#endif\n\nint foo(void);\n\n#ifdef __cplusplus\n}\n#endif\n\n#endif // FOO_H\n"
+        );
+    }
+
+    #[test]
+    fn standards() {
+        let flags = || {
+            DeclarationBuilder::new()
+                .declarator_with_initializer(
+                    TypeBuilder::new().constant().unsigned_int(),
+                    "flags",
+                    Initializer::expression(literal(Literal::Integer(
+                        IntegerConstant::new(5, IntegerSuffix::Unsigned).radix(Radix::Binary),
+                    ))),
+                )
+                .unwrap()
+                .finish()
+                .unwrap()
+        };
+        let h = HeaderBuilder::new()
+            .guard("FOO_H")
+            .unwrap()
+            .standard(Standard::C89)
+            .item(flags())
+            .finish()
+            .unwrap();
+        assert_eq!(
+            format!("{}", h),
+            "#ifndef FOO_H\n#define FOO_H\n\nconst unsigned int flags = 0x5U;\n\n#endif /* FOO_H */\n"
+        );
+        let t = TranslationUnitBuilder::new()
+            .standard(Standard::Gnu89)
+            .item(flags())
+            .finish()
+            .unwrap();
+        assert_eq!(format!("{}", t), "const unsigned int flags = 0b101U;\n");
+
+        let wide = || {
+            DeclarationBuilder::new()
+                .declarator(TypeBuilder::new().long_long(), "wide")
+                .unwrap()
+                .finish()
+                .unwrap()
+        };
+        let error = TranslationUnitBuilder::new()
+            .standard(Standard::C89)
+            .item(wide())
+            .finish()
+            .unwrap_err();
+        assert_eq!(error.to_string(), "long long is not supported in C89");
+        TranslationUnitBuilder::new()
+            .standard(Standard::Gnu89)
+            .item(wide())
+            .finish()
+            .unwrap();
+    }
+
+    #[test]
+    fn standard_spellings() {
+        let unit = |standard| {
+            let check = StaticAssert::new(
+                equal(
+                    sizeof_type(TypeBuilder::new().bool()),
+                    literal(Literal::Signed(1)),
+                ),
+                None,
+            );
+            let align = DeclarationBuilder::new()
+                .declarator_with_initializer(
+                    TypeBuilder::new().int(),
+                    "align",
+                    Initializer::expression(alignof_type(TypeBuilder::new().underscore_bool())),
+                )
+                .unwrap()
+                .finish()
+                .unwrap();
+            TranslationUnitBuilder::new()
+                .standard(standard)
+                .item(check)
+                .item(align)
+                .finish()
+                .map(|t| t.to_string())
+        };
+        assert_eq!(
+            unit(Standard::C11).unwrap(),
+            "_Static_assert(sizeof(_Bool) == 1, \"sizeof(_Bool) == 1\");\nint align = _Alignof(_Bool);\n"
+        );
+        assert_eq!(
+            unit(Standard::C23).unwrap(),
+            "static_assert(sizeof(bool) == 1);\nint align = alignof(bool);\n"
+        );
+        unit(Standard::C99).unwrap_err();
+    }
+
+    #[test]
+    fn c89_rejections() {
+        let designated = DeclarationBuilder::new()
+            .declarator_with_initializer(
+                TypeBuilder::new().alias_named("point_t").unwrap(),
+                "origin",
+                Initializer::list(vec![Initializer::designated(
+                    vec![Designator::field("x").unwrap()],
+                    Initializer::expression(literal(Literal::Signed(0))),
+                )
+                .unwrap()]),
+            )
+            .unwrap()
+            .finish()
+            .unwrap();
+        TranslationUnitBuilder::new()
+            .standard(Standard::C89)
+            .item(designated)
+            .finish()
+            .unwrap_err();
+
+        let body = |first: BlockItem, second: BlockItem| {
+            FunctionBuilder::new(TypeBuilder::new().void(), "f")
+                .unwrap()
+                .finish()
+                .unwrap()
+                .define(vec![first, second])
+                .unwrap()
+        };
+        let declaration = || {
+            DeclarationBuilder::new()
+                .declarator(TypeBuilder::new().int(), "x")
+                .unwrap()
+                .finish()
+                .unwrap()
+                .into()
+        };
+        let statement = || r#return(None).into();
+        TranslationUnitBuilder::new()
+            .standard(Standard::C89)
+            .item(body(declaration(), statement()))
+            .finish()
+            .unwrap();
+        TranslationUnitBuilder::new()
+            .standard(Standard::C89)
+            .item(body(statement(), declaration()))
+            .finish()
+            .unwrap_err();
+        TranslationUnitBuilder::new()
+            .standard(Standard::C99)
+            .item(body(statement(), declaration()))
+            .finish()
+            .unwrap();
+    }
+
+    #[test]
+    fn dialects() {
+        let unit = |standard, dialect| {
+            let wire = TypeBuilder::new()
+                .struct_named("wire")
+                .unwrap()
+                .with_fields()
+                .named_field(TypeBuilder::new().unsigned_char(), "tag")
+                .unwrap()
+                .named_field(TypeBuilder::new().unsigned_int(), "length")
+                .unwrap()
+                .attribute(Attribute::Packed)
+                .unwrap()
+                .finish()
+                .unwrap();
+            let buffer = DeclarationBuilder::new()
+                .storage(Storage::Static)
+                .attribute(Attribute::aligned(16).unwrap())
+                .declarator(
+                    TypeBuilder::new()
+                        .sized_array_of(literal(Literal::Signed(64)))
+                        .unsigned_char(),
+                    "buffer",
+                )
+                .unwrap()
+                .finish()
+                .unwrap();
+            TranslationUnitBuilder::new()
+                .standard(standard)
+                .dialect(dialect)
+                .item(Declaration::type_only(wire).unwrap())
+                .item(buffer)
+                .finish()
+                .map(|t| t.to_string())
+        };
+        assert_eq!(
+            unit(Standard::C99, Dialect::Gnu).unwrap(),
+            "struct __attribute__((packed)) wire { unsigned char tag; unsigned int length; };\nstatic __attribute__((aligned(16))) unsigned char buffer[64];\n"
+        );
+        assert_eq!(
+            unit(Standard::C11, Dialect::Msvc).unwrap(),
+            "__pragma(pack(push, 1)) struct wire { unsigned char tag; unsigned int length; } __pragma(pack(pop));\nstatic __declspec(align(16)) unsigned char buffer[64];\n"
+        );
+        assert!(matches!(
+            unit(Standard::C23, Dialect::Standard),
+            Err(Error::UnsupportedAttribute(_, Dialect::Standard))
+        ));
+        assert!(matches!(
+            TranslationUnitBuilder::new()
+                .dialect(Dialect::Msvc)
+                .finish(),
+            Err(Error::MissingStandard(Dialect::Msvc))
+        ));
+        DeclarationBuilder::new()
+            .attribute(Attribute::Packed)
+            .declarator(TypeBuilder::new().int(), "counter")
+            .unwrap()
+            .finish()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn export_macro() {
+        let helper = FunctionBuilder::new(TypeBuilder::new().int(), "helper")
+            .unwrap()
+            .storage(Storage::Static)
+            .unwrap()
+            .finish()
+            .unwrap();
+        let windows = ConditionalBuilder::ifdef("_WIN32")
+            .unwrap()
+            .item(
+                FunctionBuilder::new(TypeBuilder::new().void(), "bar")
+                    .unwrap()
+                    .storage(Storage::Extern)
+                    .unwrap()
+                    .finish()
+                    .unwrap(),
+            )
+            .finish();
+        let h = HeaderBuilder::new()
+            .export_macro("MYLIB_API", "MYLIB_BUILD")
+            .unwrap()
+            .item(prototype())
+            .item(helper)
+            .item(windows)
+            .finish()
+            .unwrap();
+        assert_eq!(
+            h.to_string(),
+            "#if defined(_WIN32) || defined(__CYGWIN__)\n#ifdef MYLIB_BUILD\n#define MYLIB_API __declspec(dllexport)\n#else\n#define MYLIB_API __declspec(dllimport)\n#endif\n#elif defined(__GNUC__)\n#define MYLIB_API __attribute__((visibility(\"default\")))\n#else\n#define MYLIB_API\n#endif\nMYLIB_API int foo(void);\nstatic int helper(void);\n#ifdef _WIN32\nextern MYLIB_API void bar(void);\n#endif\n"
+        );
+        HeaderBuilder::new()
+            .export_macro("MYLIB_API", "2")
+            .unwrap_err();
     }
 }
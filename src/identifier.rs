@@ -1,12 +1,418 @@
 use crate::error::{Error, Result};
+use crate::standard::{is_cplusplus_keyword, Standard};
+use std::collections::{HashMap, HashSet};
 
-#[derive(Debug)]
+// How characters outside of ASCII are written out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnicodeSpelling {
+    Raw,
+    // `\uXXXX` universal character names, for compilers that only accept ASCII source
+    Escaped,
+}
+
+#[derive(Debug, Clone)]
 pub struct Identifier {
     name: String,
+    spelling: UnicodeSpelling,
+}
+
+// The spelling doesn't change which identifier a name denotes
+impl PartialEq for Identifier {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Identifier {}
+
+impl std::hash::Hash for Identifier {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+
+// Characters allowed in identifiers in C99 (C99 annex D), a much narrower set than C11's
+const C99_RANGES: &[(u32, u32)] = &[
+    // Latin and special characters
+    (0x00aa, 0x00aa),
+    (0x00b5, 0x00b5),
+    (0x00b7, 0x00b7),
+    (0x00ba, 0x00ba),
+    (0x00c0, 0x00d6),
+    (0x00d8, 0x00f6),
+    (0x00f8, 0x01f5),
+    (0x01fa, 0x0217),
+    (0x0250, 0x02a8),
+    (0x02b0, 0x02b8),
+    (0x02bb, 0x02bb),
+    (0x02bd, 0x02c1),
+    (0x02d0, 0x02d1),
+    (0x02e0, 0x02e4),
+    (0x037a, 0x037a),
+    // Greek
+    (0x0386, 0x0386),
+    (0x0388, 0x038a),
+    (0x038c, 0x038c),
+    (0x038e, 0x03a1),
+    (0x03a3, 0x03ce),
+    (0x03d0, 0x03d6),
+    (0x03da, 0x03da),
+    (0x03dc, 0x03dc),
+    (0x03de, 0x03de),
+    (0x03e0, 0x03e0),
+    (0x03e2, 0x03f3),
+    // Cyrillic
+    (0x0401, 0x040c),
+    (0x040e, 0x044f),
+    (0x0451, 0x045c),
+    (0x045e, 0x0481),
+    (0x0490, 0x04c4),
+    (0x04c7, 0x04c8),
+    (0x04cb, 0x04cc),
+    (0x04d0, 0x04eb),
+    (0x04ee, 0x04f5),
+    (0x04f8, 0x04f9),
+    // Armenian
+    (0x0531, 0x0556),
+    (0x0559, 0x0559),
+    (0x0561, 0x0587),
+    // Hebrew
+    (0x05b0, 0x05b9),
+    (0x05bb, 0x05bd),
+    (0x05bf, 0x05bf),
+    (0x05c1, 0x05c2),
+    (0x05d0, 0x05ea),
+    (0x05f0, 0x05f2),
+    // Arabic
+    (0x0621, 0x063a),
+    (0x0640, 0x0652),
+    (0x0660, 0x0669),
+    (0x0670, 0x06b7),
+    (0x06ba, 0x06be),
+    (0x06c0, 0x06ce),
+    (0x06d0, 0x06dc),
+    (0x06e5, 0x06e8),
+    (0x06ea, 0x06ed),
+    (0x06f0, 0x06f9),
+    // Devanagari
+    (0x0901, 0x0903),
+    (0x0905, 0x0939),
+    (0x093d, 0x094d),
+    (0x0950, 0x0952),
+    (0x0958, 0x0963),
+    (0x0966, 0x096f),
+    // Bengali
+    (0x0981, 0x0983),
+    (0x0985, 0x098c),
+    (0x098f, 0x0990),
+    (0x0993, 0x09a8),
+    (0x09aa, 0x09b0),
+    (0x09b2, 0x09b2),
+    (0x09b6, 0x09b9),
+    (0x09be, 0x09c4),
+    (0x09c7, 0x09c8),
+    (0x09cb, 0x09cd),
+    (0x09dc, 0x09dd),
+    (0x09df, 0x09e3),
+    (0x09e6, 0x09f1),
+    // Gurmukhi
+    (0x0a02, 0x0a02),
+    (0x0a05, 0x0a0a),
+    (0x0a0f, 0x0a10),
+    (0x0a13, 0x0a28),
+    (0x0a2a, 0x0a30),
+    (0x0a32, 0x0a33),
+    (0x0a35, 0x0a36),
+    (0x0a38, 0x0a39),
+    (0x0a3e, 0x0a42),
+    (0x0a47, 0x0a48),
+    (0x0a4b, 0x0a4d),
+    (0x0a59, 0x0a5c),
+    (0x0a5e, 0x0a5e),
+    (0x0a66, 0x0a6f),
+    (0x0a74, 0x0a74),
+    // Gujarati
+    (0x0a81, 0x0a83),
+    (0x0a85, 0x0a8b),
+    (0x0a8d, 0x0a8d),
+    (0x0a8f, 0x0a91),
+    (0x0a93, 0x0aa8),
+    (0x0aaa, 0x0ab0),
+    (0x0ab2, 0x0ab3),
+    (0x0ab5, 0x0ab9),
+    (0x0abd, 0x0ac5),
+    (0x0ac7, 0x0ac9),
+    (0x0acb, 0x0acd),
+    (0x0ad0, 0x0ad0),
+    (0x0ae0, 0x0ae0),
+    (0x0ae6, 0x0aef),
+    // Oriya
+    (0x0b01, 0x0b03),
+    (0x0b05, 0x0b0c),
+    (0x0b0f, 0x0b10),
+    (0x0b13, 0x0b28),
+    (0x0b2a, 0x0b30),
+    (0x0b32, 0x0b33),
+    (0x0b36, 0x0b39),
+    (0x0b3d, 0x0b43),
+    (0x0b47, 0x0b48),
+    (0x0b4b, 0x0b4d),
+    (0x0b5c, 0x0b5d),
+    (0x0b5f, 0x0b61),
+    (0x0b66, 0x0b6f),
+    // Tamil
+    (0x0b82, 0x0b83),
+    (0x0b85, 0x0b8a),
+    (0x0b8e, 0x0b90),
+    (0x0b92, 0x0b95),
+    (0x0b99, 0x0b9a),
+    (0x0b9c, 0x0b9c),
+    (0x0b9e, 0x0b9f),
+    (0x0ba3, 0x0ba4),
+    (0x0ba8, 0x0baa),
+    (0x0bae, 0x0bb5),
+    (0x0bb7, 0x0bb9),
+    (0x0bbe, 0x0bc2),
+    (0x0bc6, 0x0bc8),
+    (0x0bca, 0x0bcd),
+    (0x0be7, 0x0bef),
+    // Telugu
+    (0x0c01, 0x0c03),
+    (0x0c05, 0x0c0c),
+    (0x0c0e, 0x0c10),
+    (0x0c12, 0x0c28),
+    (0x0c2a, 0x0c33),
+    (0x0c35, 0x0c39),
+    (0x0c3e, 0x0c44),
+    (0x0c46, 0x0c48),
+    (0x0c4a, 0x0c4d),
+    (0x0c60, 0x0c61),
+    (0x0c66, 0x0c6f),
+    // Kannada
+    (0x0c82, 0x0c83),
+    (0x0c85, 0x0c8c),
+    (0x0c8e, 0x0c90),
+    (0x0c92, 0x0ca8),
+    (0x0caa, 0x0cb3),
+    (0x0cb5, 0x0cb9),
+    (0x0cbe, 0x0cc4),
+    (0x0cc6, 0x0cc8),
+    (0x0cca, 0x0ccd),
+    (0x0cde, 0x0cde),
+    (0x0ce0, 0x0ce1),
+    (0x0ce6, 0x0cef),
+    // Malayalam
+    (0x0d02, 0x0d03),
+    (0x0d05, 0x0d0c),
+    (0x0d0e, 0x0d10),
+    (0x0d12, 0x0d28),
+    (0x0d2a, 0x0d39),
+    (0x0d3e, 0x0d43),
+    (0x0d46, 0x0d48),
+    (0x0d4a, 0x0d4d),
+    (0x0d60, 0x0d61),
+    (0x0d66, 0x0d6f),
+    // Thai
+    (0x0e01, 0x0e3a),
+    (0x0e40, 0x0e5b),
+    // Lao
+    (0x0e81, 0x0e82),
+    (0x0e84, 0x0e84),
+    (0x0e87, 0x0e88),
+    (0x0e8a, 0x0e8a),
+    (0x0e8d, 0x0e8d),
+    (0x0e94, 0x0e97),
+    (0x0e99, 0x0e9f),
+    (0x0ea1, 0x0ea3),
+    (0x0ea5, 0x0ea5),
+    (0x0ea7, 0x0ea7),
+    (0x0eaa, 0x0eab),
+    (0x0ead, 0x0eae),
+    (0x0eb0, 0x0eb9),
+    (0x0ebb, 0x0ebd),
+    (0x0ec0, 0x0ec4),
+    (0x0ec6, 0x0ec6),
+    (0x0ec8, 0x0ecd),
+    (0x0ed0, 0x0ed9),
+    (0x0edc, 0x0edd),
+    // Tibetan
+    (0x0f00, 0x0f00),
+    (0x0f18, 0x0f19),
+    (0x0f20, 0x0f33),
+    (0x0f35, 0x0f35),
+    (0x0f37, 0x0f37),
+    (0x0f39, 0x0f39),
+    (0x0f3e, 0x0f47),
+    (0x0f49, 0x0f69),
+    (0x0f71, 0x0f84),
+    (0x0f86, 0x0f8b),
+    (0x0f90, 0x0f95),
+    (0x0f97, 0x0f97),
+    (0x0f99, 0x0fad),
+    (0x0fb1, 0x0fb7),
+    (0x0fb9, 0x0fb9),
+    // Georgian
+    (0x10a0, 0x10c5),
+    (0x10d0, 0x10f6),
+    // Latin and Greek extended
+    (0x1e00, 0x1e9b),
+    (0x1ea0, 0x1ef9),
+    (0x1f00, 0x1f15),
+    (0x1f18, 0x1f1d),
+    (0x1f20, 0x1f45),
+    (0x1f48, 0x1f4d),
+    (0x1f50, 0x1f57),
+    (0x1f59, 0x1f59),
+    (0x1f5b, 0x1f5b),
+    (0x1f5d, 0x1f5d),
+    (0x1f5f, 0x1f7d),
+    (0x1f80, 0x1fb4),
+    (0x1fb6, 0x1fbc),
+    (0x1fbe, 0x1fbe),
+    (0x1fc2, 0x1fc4),
+    (0x1fc6, 0x1fcc),
+    (0x1fd0, 0x1fd3),
+    (0x1fd6, 0x1fdb),
+    (0x1fe0, 0x1fec),
+    (0x1ff2, 0x1ff4),
+    (0x1ff6, 0x1ffc),
+    // Special characters
+    (0x203f, 0x2040),
+    (0x207f, 0x207f),
+    (0x2102, 0x2102),
+    (0x2107, 0x2107),
+    (0x210a, 0x2113),
+    (0x2115, 0x2115),
+    (0x2118, 0x211d),
+    (0x2124, 0x2124),
+    (0x2126, 0x2126),
+    (0x2128, 0x2128),
+    (0x212a, 0x2131),
+    (0x2133, 0x2138),
+    (0x2160, 0x2182),
+    (0x3005, 0x3007),
+    (0x3021, 0x3029),
+    // Hiragana, Katakana and Bopomofo
+    (0x3041, 0x3093),
+    (0x309b, 0x309c),
+    (0x30a1, 0x30f6),
+    (0x30fb, 0x30fc),
+    (0x3105, 0x312c),
+    // CJK unified ideographs and Hangul
+    (0x4e00, 0x9fa5),
+    (0xac00, 0xd7a3),
+];
+
+// The digits of C99 annex D, which may not start an identifier
+const C99_DIGIT_RANGES: &[(u32, u32)] = &[
+    (0x0660, 0x0669),
+    (0x06f0, 0x06f9),
+    (0x0966, 0x096f),
+    (0x09e6, 0x09ef),
+    (0x0a66, 0x0a6f),
+    (0x0ae6, 0x0aef),
+    (0x0b66, 0x0b6f),
+    (0x0be7, 0x0bef),
+    (0x0c66, 0x0c6f),
+    (0x0ce6, 0x0cef),
+    (0x0d66, 0x0d6f),
+    (0x0e50, 0x0e59),
+    (0x0ed0, 0x0ed9),
+    (0x0f20, 0x0f33),
+];
+
+// Characters allowed in identifiers in C11 and C17 (C11 annex D.1)
+const EXTENDED_RANGES: &[(u32, u32)] = &[
+    (0x00a8, 0x00a8),
+    (0x00aa, 0x00aa),
+    (0x00ad, 0x00ad),
+    (0x00af, 0x00af),
+    (0x00b2, 0x00b5),
+    (0x00b7, 0x00ba),
+    (0x00bc, 0x00be),
+    (0x00c0, 0x00d6),
+    (0x00d8, 0x00f6),
+    (0x00f8, 0x00ff),
+    (0x0100, 0x167f),
+    (0x1681, 0x180d),
+    (0x180f, 0x1fff),
+    (0x200b, 0x200d),
+    (0x202a, 0x202e),
+    (0x203f, 0x2040),
+    (0x2054, 0x2054),
+    (0x2060, 0x206f),
+    (0x2070, 0x218f),
+    (0x2460, 0x24ff),
+    (0x2776, 0x2793),
+    (0x2c00, 0x2dff),
+    (0x2e80, 0x2fff),
+    (0x3004, 0x3007),
+    (0x3021, 0x302f),
+    (0x3031, 0x303f),
+    (0x3040, 0xd7ff),
+    (0xf900, 0xfd3d),
+    (0xfd40, 0xfdcf),
+    (0xfdf0, 0xfe44),
+    (0xfe47, 0xfffd),
+];
+
+// Combining characters that may not start an identifier (C11 annex D.2)
+const INITIAL_DISALLOWED_RANGES: &[(u32, u32)] = &[
+    (0x0300, 0x036f),
+    (0x1dc0, 0x1dff),
+    (0x20d0, 0x20ff),
+    (0xfe20, 0xfe2f),
+];
+
+fn in_ranges(c: char, ranges: &[(u32, u32)]) -> bool {
+    ranges
+        .iter()
+        .any(|&(low, high)| (low..=high).contains(&(c as u32)))
+}
+
+// Whether a character outside of ASCII may appear in an identifier
+fn is_extended_character(c: char, first: bool, standard: Standard) -> bool {
+    match standard {
+        Standard::C89 | Standard::Gnu89 => false,
+        Standard::C99 | Standard::Gnu99 => {
+            in_ranges(c, C99_RANGES) && !(first && in_ranges(c, C99_DIGIT_RANGES))
+        }
+        // Astral planes are allowed up to the last two code points of each
+        Standard::C11 | Standard::C17 | Standard::Gnu11 | Standard::Gnu17 => {
+            let allowed = in_ranges(c, EXTENDED_RANGES)
+                || (c as u32 >= 0x10000 && c as u32 & 0xffff <= 0xfffd);
+            allowed && !(first && in_ranges(c, INITIAL_DISALLOWED_RANGES))
+        }
+        Standard::C23 | Standard::Gnu23 if first => unicode_ident::is_xid_start(c),
+        Standard::C23 | Standard::Gnu23 => unicode_ident::is_xid_continue(c),
+    }
+}
+
+fn is_reserved(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next() == Some('_')
+        && chars
+            .next()
+            .is_some_and(|c| c == '_' || c.is_ascii_uppercase())
 }
 
 impl Identifier {
+    // Rejects the keywords of every revision, so the name is usable whatever the target
     pub fn new<S: Into<String>>(name: S) -> Result<Self> {
+        Self::for_standard(name, Standard::C23)
+    }
+
+    // Rejects only the keywords of the given revision, e.g. `bool` is a valid name before C23
+    pub fn for_standard<S: Into<String>>(name: S, standard: Standard) -> Result<Self> {
+        let identifier = Self::macro_name(name)?;
+        if standard.is_keyword(&identifier.name) {
+            return Err(Error::KeywordIdentifier(identifier.name, standard));
+        }
+        Ok(identifier)
+    }
+
+    // Preprocessor names aren't checked against keywords, which are only identifiers to it
+    pub(crate) fn macro_name<S: Into<String>>(name: S) -> Result<Self> {
         let name = name.into();
         if name.is_empty() {
             return Err(Error::BadIdentifier(name));
@@ -21,13 +427,134 @@ impl Identifier {
         {
             return Err(Error::BadIdentifier(name));
         }
-        Ok(Self { name: name })
+        Ok(Self {
+            name,
+            spelling: UnicodeSpelling::Raw,
+        })
+    }
+
+    // Also accepts the characters outside of ASCII that the given revision allows
+    pub fn unicode<S: Into<String>>(
+        name: S,
+        standard: Standard,
+        spelling: UnicodeSpelling,
+    ) -> Result<Self> {
+        let name = name.into();
+        let valid = name.chars().enumerate().all(|(i, c)| {
+            if c.is_ascii() {
+                c.is_ascii_alphabetic() || c == '_' || (i > 0 && c.is_ascii_digit())
+            } else {
+                is_extended_character(c, i == 0, standard)
+            }
+        });
+        if name.is_empty() || !valid {
+            return Err(Error::BadIdentifier(name));
+        }
+        if standard.is_keyword(&name) {
+            return Err(Error::KeywordIdentifier(name, standard));
+        }
+        Ok(Self { name, spelling })
+    }
+
+    // Deterministically turn any string into a valid name: punctuation becomes `_`, other
+    // characters are spelled as `_uXXXX`, leading underscores are dropped as they are reserved
+    // at file scope, runs of underscores are squeezed as C++ reserves `__` anywhere, a leading
+    // digit or escape gets an `n` prefix, and keywords of C or C++ get a trailing `_`
+    pub fn sanitize(name: &str) -> Self {
+        let mut sanitized = String::new();
+        for c in name.chars() {
+            if c.is_ascii_alphanumeric() {
+                sanitized.push(c);
+            } else if c.is_ascii() {
+                if !sanitized.is_empty() && !sanitized.ends_with('_') {
+                    sanitized.push('_');
+                }
+            } else {
+                if sanitized.is_empty() {
+                    sanitized.push('n');
+                }
+                if !sanitized.ends_with('_') {
+                    sanitized.push('_');
+                }
+                sanitized.push_str(&format!("u{:04X}", c as u32));
+            }
+        }
+        if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+            sanitized.insert(0, 'n');
+        }
+        if Standard::C23.is_keyword(&sanitized) || is_cplusplus_keyword(&sanitized) {
+            sanitized.push('_');
+        }
+        Self {
+            name: sanitized,
+            spelling: UnicodeSpelling::Raw,
+        }
+    }
+
+    // Names starting with a double underscore or an underscore and a capital are reserved
+    pub fn is_reserved(&self) -> bool {
+        is_reserved(&self.name)
+    }
+
+    // Opt-in check for names that belong to the implementation
+    pub fn unreserved(self) -> Result<Self> {
+        if self.is_reserved() {
+            Err(Error::ReservedIdentifier(self.name))
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+// Hands out names that are unique within a scope; clone it to start a nested scope
+#[derive(Debug, Clone, Default)]
+pub struct NameAllocator {
+    used: HashSet<String>,
+    next: HashMap<String, usize>,
+}
+
+impl NameAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Mark a name that is already in use so it is never handed out
+    pub fn reserve(&mut self, name: &Identifier) {
+        self.used.insert(name.name.clone());
+    }
+
+    // The sanitized base if it is free, otherwise the first free `base_1`, `base_2`, ...
+    pub fn allocate(&mut self, base: &str) -> Identifier {
+        let base = Identifier::sanitize(base).name;
+        let mut name = base.clone();
+        while self.used.contains(&name) {
+            let next = self.next.entry(base.clone()).or_insert(1);
+            name = format!("{}_{}", base, next);
+            *next += 1;
+        }
+        // Sanitized names never start with an underscore, so neither do their suffixed forms
+        debug_assert!(!is_reserved(&name));
+        self.used.insert(name.clone());
+        Identifier {
+            name,
+            spelling: UnicodeSpelling::Raw,
+        }
     }
 }
 
 impl std::fmt::Display for Identifier {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.name)
+        if self.spelling == UnicodeSpelling::Raw || self.name.is_ascii() {
+            return f.write_str(&self.name);
+        }
+        for c in self.name.chars() {
+            match c {
+                '\0'..='\x7f' => write!(f, "{}", c)?,
+                '\u{80}'..='\u{ffff}' => write!(f, "\\u{:04x}", c as u32)?,
+                _ => write!(f, "\\U{:08x}", c as u32)?,
+            }
+        }
+        Ok(())
     }
 }
 
@@ -50,4 +577,106 @@ mod tests {
     fn bad_char() {
         Identifier::new("hello/world").unwrap_err();
     }
+
+    #[test]
+    fn keywords() {
+        assert!(matches!(
+            Identifier::new("return"),
+            Err(Error::KeywordIdentifier(_, Standard::C23))
+        ));
+        Identifier::new("_Bool").unwrap_err();
+        Identifier::new("bool").unwrap_err();
+        Identifier::for_standard("bool", Standard::C99).unwrap();
+        Identifier::for_standard("restrict", Standard::C89).unwrap();
+        Identifier::for_standard("default", Standard::C89).unwrap_err();
+    }
+
+    #[test]
+    fn unicode() {
+        let raw = Identifier::unicode("größe", Standard::C99, UnicodeSpelling::Raw).unwrap();
+        assert_eq!(format!("{}", raw), "größe");
+        let escaped =
+            Identifier::unicode("größe", Standard::C99, UnicodeSpelling::Escaped).unwrap();
+        assert_eq!(format!("{}", escaped), "gr\\u00f6\\u00dfe");
+        assert_eq!(raw, escaped);
+        let astral = Identifier::unicode("x𝑦", Standard::C11, UnicodeSpelling::Escaped).unwrap();
+        assert_eq!(format!("{}", astral), "x\\U0001d466");
+
+        Identifier::unicode("größe", Standard::C89, UnicodeSpelling::Raw).unwrap_err();
+        // C99 has no astral characters, and only the letters and digits of its annex D
+        Identifier::unicode("x𝑦", Standard::C99, UnicodeSpelling::Raw).unwrap_err();
+        Identifier::unicode("x\u{a8}", Standard::C11, UnicodeSpelling::Raw).unwrap();
+        Identifier::unicode("x\u{a8}", Standard::Gnu99, UnicodeSpelling::Raw).unwrap_err();
+        Identifier::unicode("x\u{663}", Standard::C99, UnicodeSpelling::Raw).unwrap();
+        Identifier::unicode("\u{663}x", Standard::C99, UnicodeSpelling::Raw).unwrap_err();
+        Identifier::unicode("\u{301}a", Standard::C11, UnicodeSpelling::Raw).unwrap_err();
+        Identifier::unicode("a\u{301}", Standard::C11, UnicodeSpelling::Raw).unwrap();
+        Identifier::unicode("x×y", Standard::C23, UnicodeSpelling::Raw).unwrap_err();
+        Identifier::unicode("x²", Standard::C23, UnicodeSpelling::Raw).unwrap_err();
+        Identifier::unicode("a‿b", Standard::C23, UnicodeSpelling::Raw).unwrap();
+        Identifier::unicode("a\u{301}", Standard::C23, UnicodeSpelling::Raw).unwrap();
+        Identifier::unicode("\u{301}a", Standard::C23, UnicodeSpelling::Raw).unwrap_err();
+        Identifier::unicode("имя", Standard::C23, UnicodeSpelling::Raw).unwrap();
+        Identifier::unicode("1имя", Standard::C23, UnicodeSpelling::Raw).unwrap_err();
+        Identifier::unicode("int", Standard::C23, UnicodeSpelling::Raw).unwrap_err();
+        Identifier::unicode("größe", Standard::Gnu89, UnicodeSpelling::Raw).unwrap_err();
+        Identifier::unicode("a‿b", Standard::Gnu23, UnicodeSpelling::Raw).unwrap();
+    }
+
+    #[test]
+    fn sanitize() {
+        let sanitize = |name| Identifier::sanitize(name).to_string();
+        assert_eq!(sanitize("my-field"), "my_field");
+        assert_eq!(sanitize("2fast"), "n2fast");
+        assert_eq!(sanitize("__x"), "x");
+        assert_eq!(sanitize("_Foo"), "Foo");
+        assert_eq!(sanitize("-Foo"), "Foo");
+        assert_eq!(sanitize("__2"), "n2");
+        assert_eq!(sanitize("__int"), "int_");
+        assert_eq!(sanitize("_foo"), "foo");
+        assert_eq!(sanitize("a--b"), "a_b");
+        assert_eq!(sanitize("class"), "class_");
+        assert_eq!(sanitize("default"), "default_");
+        assert_eq!(sanitize("größe"), "gr_u00F6_u00DFe");
+        assert_eq!(sanitize("éclair"), "n_u00E9clair");
+        assert_eq!(sanitize(""), "n");
+        assert_eq!(sanitize("ok_name"), "ok_name");
+    }
+
+    #[test]
+    fn name_allocator() {
+        let mut names = NameAllocator::new();
+        names.reserve(&Identifier::new("tmp_2").unwrap());
+        assert_eq!(names.allocate("tmp").to_string(), "tmp");
+        assert_eq!(names.allocate("tmp").to_string(), "tmp_1");
+        assert_eq!(names.allocate("tmp").to_string(), "tmp_3");
+        assert_eq!(names.allocate("tmp_1").to_string(), "tmp_1_1");
+        assert_eq!(names.allocate("int").to_string(), "int_");
+
+        let mut inner = names.clone();
+        assert_eq!(inner.allocate("tmp").to_string(), "tmp_4");
+        assert_eq!(names.allocate("x").to_string(), "x");
+
+        let mut names = NameAllocator::new();
+        assert_eq!(names.allocate("").to_string(), "n");
+        assert_eq!(names.allocate("").to_string(), "n_1");
+        assert_eq!(names.allocate("éclair").to_string(), "n_u00E9clair");
+        assert!(!names.allocate("_").is_reserved());
+    }
+
+    #[test]
+    fn reserved() {
+        let reserved = Identifier::new("__reserved").unwrap();
+        assert!(reserved.is_reserved());
+        assert!(matches!(
+            reserved.unreserved(),
+            Err(Error::ReservedIdentifier(_))
+        ));
+        Identifier::new("_Upper").unwrap().unreserved().unwrap_err();
+        Identifier::new("_lower").unwrap().unreserved().unwrap();
+        Identifier::new("plain__name")
+            .unwrap()
+            .unreserved()
+            .unwrap();
+    }
 }
@@ -0,0 +1,227 @@
+use crate::attribute::Dialect;
+use crate::error::{Error, Result};
+use crate::expression::{Expression, Precedence};
+use crate::identifier::Identifier;
+use crate::standard::Standard;
+
+#[derive(Debug)]
+pub enum Designator {
+    Field(Identifier),
+    Index(Expression),
+}
+
+impl Designator {
+    pub fn field<S: Into<String>>(name: S) -> Result<Self> {
+        Ok(Designator::Field(Identifier::new(name.into())?))
+    }
+
+    pub fn index(index: Expression) -> Self {
+        Designator::Index(index)
+    }
+}
+
+impl std::fmt::Display for Designator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Designator::Field(name) => write!(f, ".{}", name),
+            Designator::Index(index) => {
+                write!(f, "[{}]", index.render_operand(Precedence::Conditional))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Initializer {
+    Expression(Expression),
+    List(Vec<Initializer>),
+    // Only valid as an element of an initializer list
+    Designated {
+        designators: Vec<Designator>,
+        initializer: Box<Initializer>,
+    },
+}
+
+impl Initializer {
+    pub fn expression(expression: Expression) -> Self {
+        Initializer::Expression(expression)
+    }
+
+    pub fn list(initializers: Vec<Initializer>) -> Self {
+        Initializer::List(initializers)
+    }
+
+    // Further designators are chained in the same list, e.g. `.a.b[2] = 0`
+    pub fn designated(designators: Vec<Designator>, initializer: Initializer) -> Result<Self> {
+        check_designated(&designators, &initializer)?;
+        Ok(Initializer::Designated {
+            designators,
+            initializer: Box::new(initializer),
+        })
+    }
+
+    pub(crate) fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        if let Initializer::Designated { .. } = self {
+            return Err(Error::MisplacedDesignator(self.to_string()));
+        }
+        self.lower_element(standard, dialect)
+    }
+
+    // Like `lower`, but for an element of a list, which may be designated
+    fn lower_element(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        match self {
+            Initializer::Expression(expression) => expression.lower(standard, dialect),
+            Initializer::List(initializers) => {
+                if initializers.is_empty() {
+                    standard.require(Standard::C23, true, "empty initializers")?;
+                }
+                initializers
+                    .iter_mut()
+                    .try_for_each(|initializer| initializer.lower_element(standard, dialect))
+            }
+            Initializer::Designated {
+                designators,
+                initializer,
+            } => {
+                // The variant can be built directly, bypassing the checks in `designated`
+                check_designated(designators, initializer)?;
+                standard.require(Standard::C99, true, "designated initializers")?;
+                for designator in designators {
+                    if let Designator::Index(index) = designator {
+                        index.lower(standard, dialect)?;
+                    }
+                }
+                initializer.lower(standard, dialect)
+            }
+        }
+    }
+}
+
+fn check_designated(designators: &[Designator], initializer: &Initializer) -> Result<()> {
+    if let Initializer::Designated { .. } = initializer {
+        return Err(Error::MisplacedDesignator(initializer.to_string()));
+    }
+    if designators.is_empty() {
+        return Err(Error::MissingDesignator(initializer.to_string()));
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for Initializer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Initializer::Expression(expression) => {
+                f.write_str(&expression.render_operand(Precedence::Assignment))
+            }
+            Initializer::List(initializers) => {
+                f.write_str("{")?;
+                for (i, initializer) in initializers.iter().enumerate() {
+                    write!(f, " {}", initializer)?;
+                    if i < initializers.len() - 1 {
+                        f.write_str(",")?;
+                    }
+                }
+                f.write_str(" }")
+            }
+            Initializer::Designated {
+                designators,
+                initializer,
+            } => {
+                for designator in designators {
+                    write!(f, "{}", designator)?;
+                }
+                write!(f, " = {}", initializer)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::{comma, identifier, literal};
+    use crate::literal::Literal;
+
+    fn int(value: i128) -> Initializer {
+        Initializer::expression(literal(Literal::Signed(value)))
+    }
+
+    #[test]
+    fn nested_lists() {
+        let i = Initializer::list(vec![
+            Initializer::list(vec![int(1), int(2)]),
+            Initializer::list(vec![int(3), int(4)]),
+            Initializer::list(Vec::new()),
+        ]);
+        assert_eq!(format!("{}", i), "{ { 1, 2 }, { 3, 4 }, { } }");
+    }
+
+    #[test]
+    fn designators() {
+        let i = Initializer::list(vec![
+            Initializer::designated(vec![Designator::index(literal(Literal::Signed(3)))], int(7))
+                .unwrap(),
+            Initializer::designated(
+                vec![
+                    Designator::field("a").unwrap(),
+                    Designator::field("b").unwrap(),
+                    Designator::index(literal(Literal::Signed(2))),
+                ],
+                Initializer::list(vec![int(0)]),
+            )
+            .unwrap(),
+            int(8),
+        ]);
+        assert_eq!(format!("{}", i), "{ [3] = 7, .a.b[2] = { 0 }, 8 }");
+
+        Initializer::designated(Vec::new(), int(1)).unwrap_err();
+        let inner = Initializer::designated(vec![Designator::field("b").unwrap()], int(1)).unwrap();
+        Initializer::designated(vec![Designator::field("a").unwrap()], inner).unwrap_err();
+    }
+
+    #[test]
+    fn unchecked_designators() {
+        let mut i = Initializer::list(vec![Initializer::Designated {
+            designators: Vec::new(),
+            initializer: Box::new(int(1)),
+        }]);
+        assert!(matches!(
+            i.lower(Standard::C23, Dialect::Gnu),
+            Err(Error::MissingDesignator(_))
+        ));
+        let inner = Initializer::designated(vec![Designator::field("b").unwrap()], int(1)).unwrap();
+        let mut i = Initializer::list(vec![Initializer::Designated {
+            designators: vec![Designator::field("a").unwrap()],
+            initializer: Box::new(inner),
+        }]);
+        assert!(matches!(
+            i.lower(Standard::C23, Dialect::Gnu),
+            Err(Error::MisplacedDesignator(_))
+        ));
+        let mut i = Initializer::designated(vec![Designator::field("a").unwrap()], int(1)).unwrap();
+        assert!(matches!(
+            i.lower(Standard::C23, Dialect::Gnu),
+            Err(Error::MisplacedDesignator(_))
+        ));
+    }
+
+    #[test]
+    fn empty_list() {
+        let mut i = Initializer::list(vec![Initializer::list(Vec::new())]);
+        i.lower(Standard::C23, Dialect::Gnu).unwrap();
+        i.lower(Standard::Gnu99, Dialect::Gnu).unwrap();
+        assert!(matches!(
+            i.lower(Standard::C17, Dialect::Gnu),
+            Err(Error::UnsupportedConstruct(_, Standard::C17))
+        ));
+    }
+
+    #[test]
+    fn comma_element() {
+        let i = Initializer::list(vec![Initializer::expression(comma(
+            identifier("a").unwrap(),
+            identifier("b").unwrap(),
+        ))]);
+        assert_eq!(format!("{}", i), "{ (a, b) }");
+    }
+}
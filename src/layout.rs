@@ -0,0 +1,1102 @@
+use std::convert::TryFrom;
+
+use crate::attribute::Attributes;
+use crate::declaration::StaticAssert;
+use crate::error::{Error, Result};
+use crate::expression::{
+    equal, literal, offsetof, sizeof_type, underscore_alignof_type, BinaryOperation, Expression,
+    UnaryOperation,
+};
+use crate::identifier::Identifier;
+use crate::literal::{IntegerConstant, IntegerSuffix, Literal};
+use crate::r#type::{
+    BaseType, Enumerator, Field, LibraryType, StructType, Type, TypeBuilder, TypeModifier,
+};
+
+// Targets whose C ABI we know how to lay out, using each platform's default compiler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Abi {
+    X86_64SysV,
+    WindowsX64,
+    I386,
+    AArch64,
+    // 32-bit Arm Linux (`arm-linux-gnueabihf`), where enums are always at least int-sized
+    ArmLinuxEabi,
+    // Bare-metal 32-bit Arm (`arm-none-eabi`), where enums are as small as their values allow
+    ArmNoneEabi,
+    RiscV32,
+    RiscV64,
+}
+
+impl Abi {
+    fn pointer(self) -> u64 {
+        match self {
+            Abi::I386 | Abi::ArmLinuxEabi | Abi::ArmNoneEabi | Abi::RiscV32 => 4,
+            _ => 8,
+        }
+    }
+
+    fn long(self) -> u64 {
+        match self {
+            Abi::X86_64SysV | Abi::AArch64 | Abi::RiscV64 => 8,
+            _ => 4,
+        }
+    }
+
+    // Naturally aligned scalars, except that i386 only aligns 8-byte ones to 4
+    fn scalar(self, size: u64) -> Layout {
+        let align = if self == Abi::I386 { size.min(4) } else { size };
+        Layout::scalar(size, align)
+    }
+
+    fn long_double(self) -> Layout {
+        match self {
+            Abi::WindowsX64 | Abi::ArmLinuxEabi | Abi::ArmNoneEabi => Layout::scalar(8, 8),
+            Abi::I386 => Layout::scalar(12, 4),
+            _ => Layout::scalar(16, 16),
+        }
+    }
+
+    fn max_align(self) -> Layout {
+        match self {
+            Abi::WindowsX64 => Layout::scalar(8, 8),
+            Abi::I386 => Layout::scalar(48, 16),
+            Abi::ArmLinuxEabi | Abi::ArmNoneEabi => Layout::scalar(16, 8),
+            _ => Layout::scalar(32, 16),
+        }
+    }
+
+    fn bit_int(self, width: u32) -> Layout {
+        let width = u64::from(width);
+        if width <= 64 {
+            return self.scalar(width.div_ceil(8).next_power_of_two());
+        }
+        // Wider values are arrays of chunks
+        let chunk = match self {
+            Abi::AArch64 => 16,
+            Abi::I386 => 4,
+            _ => 8,
+        };
+        Layout::scalar(width.div_ceil(chunk * 8) * chunk, chunk)
+    }
+
+    // MSVC packs bit-fields into units of their declared type, which are never shared by types
+    // of a different size
+    fn ms_bitfields(self) -> bool {
+        self == Abi::WindowsX64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLayout {
+    name: Option<Identifier>,
+    offset: u64,
+    size: u64,
+    bit_offset: Option<u64>,
+    bit_width: Option<u64>,
+}
+
+impl FieldLayout {
+    pub fn name(&self) -> Option<&Identifier> {
+        self.name.as_ref()
+    }
+
+    // In bytes; for bit-fields, the byte holding the first bit
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    // In bits from the start of the struct, for bit-fields only
+    pub fn bit_offset(&self) -> Option<u64> {
+        self.bit_offset
+    }
+
+    pub fn bit_width(&self) -> Option<u64> {
+        self.bit_width
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    size: u64,
+    align: u64,
+    fields: Vec<FieldLayout>,
+}
+
+impl Layout {
+    fn scalar(size: u64, align: u64) -> Self {
+        Self {
+            size,
+            align,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn align(&self) -> u64 {
+        self.align
+    }
+
+    // The members of a struct or union, in declaration order
+    pub fn fields(&self) -> &[FieldLayout] {
+        &self.fields
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldLayout> {
+        self.fields
+            .iter()
+            .find(|field| field.name.as_ref().is_some_and(|n| n.to_string() == name))
+    }
+
+    // Lock-free atomics are aligned to their size
+    fn atomic(mut self, atomic: bool) -> Self {
+        if atomic && self.size.is_power_of_two() && self.size <= 16 {
+            self.align = self.size;
+        }
+        self
+    }
+}
+
+fn align_to(value: u64, align: u64) -> u64 {
+    value.div_ceil(align) * align
+}
+
+impl Type {
+    pub fn layout(&self, abi: Abi) -> Result<Layout> {
+        layout_of(self, &self.modifiers, abi)
+    }
+
+    // Static assertions that the compiler agrees with the computed layout of a named struct or
+    // union, for its size, alignment and the offset of each member that isn't a bit-field. The
+    // offset checks need <stddef.h>, as reported by `StaticAssert::required_includes`
+    pub fn layout_assertions(&self, abi: Abi) -> Result<Vec<StaticAssert>> {
+        let tagged = || -> Result<Type> {
+            match &self.base {
+                BaseType::Struct {
+                    name: Some(name),
+                    struct_type: StructType::Struct,
+                    ..
+                } if self.modifiers.is_empty() => {
+                    Ok(TypeBuilder::new().struct_named(name.to_string())?.finish())
+                }
+                BaseType::Struct {
+                    name: Some(name),
+                    struct_type: StructType::Union,
+                    ..
+                } if self.modifiers.is_empty() => {
+                    Ok(TypeBuilder::new().union_named(name.to_string())?.finish())
+                }
+                _ => Err(Error::UnnamedType(self.to_string())),
+            }
+        };
+        let constant = |value: u64| {
+            literal(Literal::Integer(IntegerConstant::new(
+                value.into(),
+                IntegerSuffix::None,
+            )))
+        };
+        let layout = self.layout(abi)?;
+        let mut assertions = vec![
+            StaticAssert::new(equal(sizeof_type(tagged()?), constant(layout.size)), None),
+            StaticAssert::new(
+                equal(underscore_alignof_type(tagged()?), constant(layout.align)),
+                None,
+            ),
+        ];
+        for field in &layout.fields {
+            if let (Some(name), None) = (&field.name, field.bit_width) {
+                assertions.push(StaticAssert::new(
+                    equal(
+                        offsetof(tagged()?, name.to_string())?,
+                        constant(field.offset),
+                    ),
+                    None,
+                ));
+            }
+        }
+        Ok(assertions)
+    }
+}
+
+// Modifiers are ordered from the name outward, so the first one is the outermost type
+fn layout_of(the_type: &Type, modifiers: &[TypeModifier], abi: Abi) -> Result<Layout> {
+    match modifiers.split_first() {
+        Some((TypeModifier::Pointer(qualifiers), _)) => {
+            Ok(Layout::scalar(abi.pointer(), abi.pointer()).atomic(qualifiers.atomic))
+        }
+        Some((TypeModifier::Array(Some(count)), rest)) => {
+            let element = layout_of(the_type, rest, abi)?;
+            let count = u64::try_from(evaluate(count, abi, &[])?)
+                .map_err(|_| Error::NonConstantExpression(count.to_string()))?;
+            let size = element
+                .size
+                .checked_mul(count)
+                .ok_or_else(|| Error::TypeTooLarge(the_type.to_string()))?;
+            Ok(Layout::scalar(size, element.align))
+        }
+        Some((TypeModifier::Array(None), _)) | Some((TypeModifier::Function(_), _)) => {
+            Err(Error::IncompleteType(the_type.to_string()))
+        }
+        None => Ok(base_layout(the_type, abi)?.atomic(the_type.qualifiers.atomic)),
+    }
+}
+
+fn base_layout(the_type: &Type, abi: Abi) -> Result<Layout> {
+    Ok(match &the_type.base {
+        BaseType::Bool
+        | BaseType::UnderscoreBool
+        | BaseType::Char
+        | BaseType::SignedChar
+        | BaseType::UnsignedChar => Layout::scalar(1, 1),
+        BaseType::Short | BaseType::UnsignedShort => Layout::scalar(2, 2),
+        BaseType::Int | BaseType::UnsignedInt | BaseType::Signed | BaseType::Unsigned => {
+            Layout::scalar(4, 4)
+        }
+        BaseType::Long | BaseType::UnsignedLong => abi.scalar(abi.long()),
+        BaseType::LongLong | BaseType::UnsignedLongLong => abi.scalar(8),
+        BaseType::Float => Layout::scalar(4, 4),
+        BaseType::Double => abi.scalar(8),
+        BaseType::LongDouble => abi.long_double(),
+        BaseType::FloatComplex => Layout::scalar(8, 4),
+        BaseType::DoubleComplex => Layout::scalar(16, abi.scalar(8).align),
+        BaseType::LongDoubleComplex => {
+            let part = abi.long_double();
+            Layout::scalar(part.size * 2, part.align)
+        }
+        BaseType::BitInt { width, .. } => abi.bit_int(*width),
+        BaseType::Library(library) => match library {
+            LibraryType::Size
+            | LibraryType::PtrDiff
+            | LibraryType::IntPtr
+            | LibraryType::UIntPtr => Layout::scalar(abi.pointer(), abi.pointer()),
+            LibraryType::WChar if abi == Abi::WindowsX64 => Layout::scalar(2, 2),
+            LibraryType::WChar => Layout::scalar(4, 4),
+            LibraryType::MaxAlign => abi.max_align(),
+            LibraryType::Int8 | LibraryType::UInt8 => Layout::scalar(1, 1),
+            LibraryType::Int16 | LibraryType::UInt16 => Layout::scalar(2, 2),
+            LibraryType::Int32 | LibraryType::UInt32 => Layout::scalar(4, 4),
+            LibraryType::Int64
+            | LibraryType::UInt64
+            | LibraryType::IntMax
+            | LibraryType::UIntMax => abi.scalar(8),
+        },
+        BaseType::Atomic(inner) => inner.layout(abi)?.atomic(true),
+        BaseType::Enum {
+            enumerators: Some(enumerators),
+            ..
+        } => enum_layout(enumerators, abi)?,
+        BaseType::Struct {
+            struct_type: StructType::Struct,
+            fields: Some(fields),
+            attributes,
+            ..
+        } => struct_layout(fields, attributes, abi)?,
+        BaseType::Struct {
+            struct_type: StructType::Union,
+            fields: Some(fields),
+            attributes,
+            ..
+        } => union_layout(fields, attributes, abi)?,
+        // Typedef names and tags without their definition can't be resolved
+        BaseType::Void
+        | BaseType::Alias(_)
+        | BaseType::Struct { fields: None, .. }
+        | BaseType::Enum {
+            enumerators: None, ..
+        } => return Err(Error::IncompleteType(the_type.to_string())),
+    })
+}
+
+// Enums are int-sized unless GCC and Clang have to widen them to hold their values, or the
+// target uses short enums
+fn enum_layout(enumerators: &[Enumerator], abi: Abi) -> Result<Layout> {
+    let mut values: Vec<(Identifier, i128)> = Vec::new();
+    let mut next = 0;
+    for enumerator in enumerators {
+        let value = match &enumerator.value {
+            Some(value) => evaluate(value, abi, &values)?,
+            None => next,
+        };
+        values.push((enumerator.name.clone(), value));
+        next = value
+            .checked_add(1)
+            .ok_or_else(|| Error::NonConstantExpression(enumerator.name.to_string()))?;
+    }
+    let min = values.iter().map(|(_, v)| *v).min().unwrap_or(0);
+    let max = values.iter().map(|(_, v)| *v).max().unwrap_or(0);
+    let fits = |bits: u32| {
+        let signed = min >= -(1 << (bits - 1)) && max < 1 << (bits - 1);
+        let unsigned = min >= 0 && max < 1 << bits;
+        signed || unsigned
+    };
+    let smallest = if abi == Abi::ArmNoneEabi { 8 } else { 32 };
+    match [8, 16, 32]
+        .iter()
+        .find(|&&bits| bits >= smallest && fits(bits))
+    {
+        Some(bits) => Ok(Layout::scalar(u64::from(*bits / 8), u64::from(*bits / 8))),
+        None if abi.ms_bitfields() => Ok(Layout::scalar(4, 4)),
+        None => Ok(abi.scalar(8)),
+    }
+}
+
+// A flexible array member takes up no space but still aligns the struct
+fn field_layout(field: &Field, abi: Abi) -> Result<Layout> {
+    match field.r#type.modifiers.split_first() {
+        Some((TypeModifier::Array(None), rest)) => {
+            let element = layout_of(&field.r#type, rest, abi)?;
+            Ok(Layout::scalar(0, element.align))
+        }
+        _ => field.r#type.layout(abi),
+    }
+}
+
+// Packing drops a member's alignment to a byte, though an explicit alignment still applies
+fn member_layout(field: &Field, packed: bool, abi: Abi) -> Result<Layout> {
+    let mut layout = field_layout(field, abi)?;
+    if packed || field.attributes.is_packed() {
+        layout.align = 1;
+    }
+    if let Some(alignment) = field.attributes.alignment() {
+        layout.align = layout.align.max(alignment);
+    }
+    Ok(layout)
+}
+
+fn bit_width(field: &Field, width: &Expression, abi: Abi) -> Result<u64> {
+    let value = u64::try_from(evaluate(width, abi, &[])?)
+        .map_err(|_| Error::NonConstantExpression(width.to_string()))?;
+    if value > field_layout(field, abi)?.size * 8 {
+        return Err(Error::NonConstantExpression(width.to_string()));
+    }
+    Ok(value)
+}
+
+fn struct_layout(fields: &[Field], attributes: &Attributes, abi: Abi) -> Result<Layout> {
+    let mut layouts = Vec::new();
+    // The end of the data placed so far, in bits
+    let mut end = 0;
+    let mut align = 1;
+    // The open MSVC storage unit, as its start and size in bits, and the bits used in it
+    let mut unit: Option<(u64, u64, u64)> = None;
+    for field in fields {
+        let packed = attributes.is_packed() || field.attributes.is_packed();
+        let layout = member_layout(field, attributes.is_packed(), abi)?;
+        let bits = layout.size * 8;
+        let bits_align = layout.align * 8;
+        let width = match &field.width {
+            Some(width) => bit_width(field, width, abi)?,
+            None => {
+                if let Some((start, size, _)) = unit.take() {
+                    end = start + size;
+                }
+                let offset = align_to(end.div_ceil(8), layout.align);
+                align = align.max(layout.align);
+                end = (offset + layout.size) * 8;
+                layouts.push(FieldLayout {
+                    name: field.name.clone(),
+                    offset,
+                    size: layout.size,
+                    bit_offset: None,
+                    bit_width: None,
+                });
+                continue;
+            }
+        };
+        let position = if abi.ms_bitfields() {
+            align = align.max(layout.align);
+            match unit {
+                // A zero-width bit-field closes the open unit
+                _ if width == 0 => {
+                    if let Some((start, size, _)) = unit.take() {
+                        end = start + size;
+                    }
+                    continue;
+                }
+                Some((start, size, used)) if size == bits && used + width <= size => {
+                    unit = Some((start, size, used + width));
+                    start + used
+                }
+                _ => {
+                    if let Some((start, size, _)) = unit.take() {
+                        end = start + size;
+                    }
+                    let start = align_to(end, bits_align);
+                    unit = Some((start, bits, width));
+                    start
+                }
+            }
+        } else {
+            // A zero-width bit-field moves to the next unit of its type
+            if width == 0 {
+                end = align_to(end, bits_align);
+                continue;
+            }
+            // Otherwise bit-fields are packed, but may not cross a unit of their type unless the
+            // struct or member is packed
+            let unit_start = end / bits_align * bits_align;
+            let position = if !packed && unit_start + bits < end + width {
+                align_to(end, bits_align)
+            } else {
+                end
+            };
+            if field.name.is_some() {
+                align = align.max(layout.align);
+            }
+            end = position + width;
+            position
+        };
+        layouts.push(FieldLayout {
+            name: field.name.clone(),
+            offset: position / 8,
+            size: layout.size,
+            bit_offset: Some(position),
+            bit_width: Some(width),
+        });
+    }
+    if let Some((start, size, _)) = unit {
+        end = start + size;
+    }
+    let align = align.max(attributes.alignment().unwrap_or(1));
+    Ok(Layout {
+        size: align_to(end.div_ceil(8), align),
+        align,
+        fields: layouts,
+    })
+}
+
+fn union_layout(fields: &[Field], attributes: &Attributes, abi: Abi) -> Result<Layout> {
+    let mut layouts = Vec::new();
+    let mut size = 0;
+    let mut align = 1;
+    for field in fields {
+        let layout = member_layout(field, attributes.is_packed(), abi)?;
+        let width = match &field.width {
+            Some(width) => Some(bit_width(field, width, abi)?),
+            None => None,
+        };
+        // Unlike MSVC, GCC and Clang ignore the type of unnamed bit-fields
+        let counts = width.is_none() || field.name.is_some() || abi.ms_bitfields();
+        if counts {
+            align = align.max(layout.align);
+        }
+        size = size.max(match width {
+            Some(width) if !abi.ms_bitfields() => width.div_ceil(8),
+            _ => layout.size,
+        });
+        layouts.push(FieldLayout {
+            name: field.name.clone(),
+            offset: 0,
+            size: layout.size,
+            bit_offset: width.map(|_| 0),
+            bit_width: width,
+        });
+    }
+    let align = align.max(attributes.alignment().unwrap_or(1));
+    Ok(Layout {
+        size: align_to(size, align),
+        align,
+        fields: layouts,
+    })
+}
+
+// An integer constant with the width in bits and signedness of its type
+#[derive(Debug, Clone, Copy)]
+struct Value {
+    value: i128,
+    bits: u64,
+    signed: bool,
+}
+
+impl Value {
+    // Unsigned values wrap around to the width of their type, but signed overflow has no value
+    fn new(value: i128, bits: u64, signed: bool) -> Option<Self> {
+        if bits == 0 || bits > 64 {
+            return None;
+        }
+        let value = if signed {
+            let limit = 1i128 << (bits - 1);
+            (-limit..limit).contains(&value).then_some(value)?
+        } else {
+            value.rem_euclid(1i128 << bits)
+        };
+        Some(Self {
+            value,
+            bits,
+            signed,
+        })
+    }
+
+    fn int(value: bool) -> Self {
+        Self {
+            value: value.into(),
+            bits: 32,
+            signed: true,
+        }
+    }
+
+    // The type of a constant is the first of `candidates` that can represent it
+    fn constant(value: u128, candidates: &[(u64, bool)]) -> Option<Self> {
+        candidates.iter().find_map(|&(bits, signed)| {
+            let magnitude = if signed { bits - 1 } else { bits };
+            (value >> magnitude == 0).then_some(Self {
+                value: value as i128,
+                bits,
+                signed,
+            })
+        })
+    }
+
+    // Integer promotion: anything narrower than int is converted to int
+    fn promote(self) -> Self {
+        if self.bits < 32 {
+            Self::int(false).with(self.value)
+        } else {
+            self
+        }
+    }
+
+    fn with(self, value: i128) -> Self {
+        Self { value, ..self }
+    }
+
+    fn convert(self, bits: u64, signed: bool) -> Option<Self> {
+        Self::new(self.value, bits, signed)
+    }
+}
+
+// The usual arithmetic conversions, giving the type both operands are converted to
+fn common_type(left: Value, right: Value) -> (u64, bool) {
+    let (left, right) = (left.promote(), right.promote());
+    if left.signed == right.signed {
+        return (left.bits.max(right.bits), left.signed);
+    }
+    let (signed, unsigned) = if left.signed {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    if unsigned.bits >= signed.bits {
+        (unsigned.bits, false)
+    } else {
+        (signed.bits, true)
+    }
+}
+
+// Convert to an integer type the way the compiler would, by truncating to its width and
+// sign-extending signed types
+fn convert(value: Value, the_type: &Type, abi: Abi) -> Option<Value> {
+    if !the_type.modifiers.is_empty() {
+        return None;
+    }
+    let signed = match &the_type.base {
+        BaseType::Bool | BaseType::UnderscoreBool => {
+            return Value::new((value.value != 0).into(), 8, false)
+        }
+        // Plain char is unsigned on Arm and RISC-V
+        BaseType::Char => !matches!(
+            abi,
+            Abi::AArch64 | Abi::ArmLinuxEabi | Abi::ArmNoneEabi | Abi::RiscV32 | Abi::RiscV64
+        ),
+        BaseType::SignedChar
+        | BaseType::Short
+        | BaseType::Int
+        | BaseType::Signed
+        | BaseType::Long
+        | BaseType::LongLong => true,
+        BaseType::UnsignedChar
+        | BaseType::UnsignedShort
+        | BaseType::UnsignedInt
+        | BaseType::Unsigned
+        | BaseType::UnsignedLong
+        | BaseType::UnsignedLongLong => false,
+        BaseType::BitInt { unsigned, .. } => !unsigned,
+        BaseType::Library(library) => match library {
+            LibraryType::PtrDiff
+            | LibraryType::IntPtr
+            | LibraryType::IntMax
+            | LibraryType::Int8
+            | LibraryType::Int16
+            | LibraryType::Int32
+            | LibraryType::Int64 => true,
+            LibraryType::Size
+            | LibraryType::UIntPtr
+            | LibraryType::UIntMax
+            | LibraryType::UInt8
+            | LibraryType::UInt16
+            | LibraryType::UInt32
+            | LibraryType::UInt64 => false,
+            LibraryType::WChar | LibraryType::MaxAlign => return None,
+        },
+        _ => return None,
+    };
+    let bits = match &the_type.base {
+        BaseType::BitInt { width, .. } => u64::from(*width),
+        _ => the_type.layout(abi).ok()?.size * 8,
+    };
+    // Wrap to the unsigned type first, so that narrowing to a signed type truncates
+    let wrapped = Value::new(value.value, bits, false)?.value;
+    let value = if signed && wrapped >> (bits - 1) == 1 {
+        wrapped - (1i128 << bits)
+    } else {
+        wrapped
+    };
+    Value::new(value, bits, signed)
+}
+
+// Evaluate an integer constant expression, given the enumerators in scope
+fn evaluate(expression: &Expression, abi: Abi, enumerators: &[(Identifier, i128)]) -> Result<i128> {
+    Ok(evaluate_typed(expression, abi, enumerators)?.value)
+}
+
+fn evaluate_typed(
+    expression: &Expression,
+    abi: Abi,
+    enumerators: &[(Identifier, i128)],
+) -> Result<Value> {
+    let fail = || Error::NonConstantExpression(expression.to_string());
+    let eval = |operand: &Expression| evaluate_typed(operand, abi, enumerators);
+    let long = abi.long() * 8;
+    let size = |value: u64| Value::new(value.into(), abi.pointer() * 8, false).ok_or_else(fail);
+    match expression {
+        Expression::Literal(value) => {
+            let signed = [(32, true), (long, true), (64, true)];
+            let unsigned = [(32, false), (long, false), (64, false)];
+            let value = match value {
+                // A negative value is rendered as a negated constant
+                Literal::Signed(v) => Value::constant(v.unsigned_abs(), &signed)
+                    .and_then(|c| Value::new(*v, c.bits, true)),
+                Literal::SignedLong(v) => Value::constant(v.unsigned_abs(), &signed[1..])
+                    .and_then(|c| Value::new(*v, c.bits, true)),
+                Literal::SignedLongLong(v) => Value::constant(v.unsigned_abs(), &signed[2..])
+                    .and_then(|c| Value::new(*v, c.bits, true)),
+                Literal::Unsigned(v) => Value::constant(*v, &unsigned),
+                Literal::UnsignedLong(v) => Value::constant(*v, &unsigned[1..]),
+                Literal::UnsignedLongLong(v) => Value::constant(*v, &unsigned[2..]),
+                Literal::Integer(constant) => {
+                    // Octal and hexadecimal constants may have an unsigned type without a suffix
+                    let either = [
+                        (32, true),
+                        (32, false),
+                        (long, true),
+                        (long, false),
+                        (64, true),
+                        (64, false),
+                    ];
+                    let candidates: &[(u64, bool)] = match constant.suffix() {
+                        IntegerSuffix::None if constant.is_decimal() => &signed,
+                        IntegerSuffix::None => &either,
+                        IntegerSuffix::Long if constant.is_decimal() => &signed[1..],
+                        IntegerSuffix::Long => &either[2..],
+                        IntegerSuffix::LongLong if constant.is_decimal() => &signed[2..],
+                        IntegerSuffix::LongLong => &either[4..],
+                        IntegerSuffix::Unsigned => &unsigned,
+                        IntegerSuffix::UnsignedLong => &unsigned[1..],
+                        IntegerSuffix::UnsignedLongLong => &unsigned[2..],
+                    };
+                    Value::constant(constant.value(), candidates)
+                }
+                Literal::Utf32Character(c) => Value::new(u32::from(*c).into(), 32, false),
+                Literal::Character(c)
+                | Literal::WideCharacter(c)
+                | Literal::Utf8Character(c)
+                | Literal::Utf16Character(c) => Some(Value::int(false).with(u32::from(*c).into())),
+                _ => None,
+            };
+            value.ok_or_else(fail)
+        }
+        // Enumerators are ints where they fit
+        Expression::Identifier(name) => enumerators
+            .iter()
+            .rev()
+            .find(|(enumerator, _)| enumerator == name)
+            .and_then(|(_, value)| {
+                Value::new(*value, 32, true).or_else(|| Value::new(*value, 64, *value < 0))
+            })
+            .ok_or_else(fail),
+        Expression::Sizeof(the_type) => size(the_type.layout(abi)?.size),
+        Expression::Alignof(the_type) | Expression::UnderscoreAlignof(the_type) => {
+            size(the_type.layout(abi)?.align)
+        }
+        Expression::Offsetof { r#type, member } => r#type
+            .layout(abi)?
+            .field(&member.to_string())
+            .filter(|field| field.bit_width.is_none())
+            .ok_or_else(fail)
+            .and_then(|field| size(field.offset)),
+        Expression::Unary {
+            expression: operand,
+            operation,
+        } => {
+            let value = eval(operand)?;
+            let promoted = value.promote();
+            let (bits, signed) = (promoted.bits, promoted.signed);
+            let value = match operation {
+                UnaryOperation::Plus => Some(promoted),
+                UnaryOperation::Cast(the_type) => convert(value, the_type, abi),
+                UnaryOperation::Minus => Value::new(-promoted.value, bits, signed),
+                UnaryOperation::BitwiseNegate => Value::new(!promoted.value, bits, signed),
+                UnaryOperation::LogicalNegate => Some(Value::int(value.value == 0)),
+                _ => None,
+            };
+            value.ok_or_else(fail)
+        }
+        Expression::Binary {
+            left,
+            right,
+            operation,
+        } => {
+            let (l, r) = (eval(left)?, eval(right)?);
+            let (bits, signed) = common_type(l, r);
+            let (a, b) = match (l.convert(bits, signed), r.convert(bits, signed)) {
+                (Some(a), Some(b)) => (a.value, b.value),
+                _ => return Err(fail()),
+            };
+            // Unsigned arithmetic wraps, so only its low bits matter
+            let arithmetic = |checked: fn(i128, i128) -> Option<i128>,
+                              wrapping: fn(i128, i128) -> i128| {
+                let value = if signed {
+                    checked(a, b)?
+                } else {
+                    wrapping(a, b)
+                };
+                Value::new(value, bits, signed)
+            };
+            // Shifts have the type of their promoted left operand
+            let shifted = l.promote();
+            let count = u32::try_from(r.value)
+                .ok()
+                .filter(|count| u64::from(*count) < shifted.bits);
+            let value = match operation {
+                BinaryOperation::Add => arithmetic(i128::checked_add, i128::wrapping_add),
+                BinaryOperation::Subtract => arithmetic(i128::checked_sub, i128::wrapping_sub),
+                BinaryOperation::Multiply => arithmetic(i128::checked_mul, i128::wrapping_mul),
+                BinaryOperation::Divide if b != 0 => Value::new(a / b, bits, signed),
+                BinaryOperation::Modulo if b != 0 => Value::new(a % b, bits, signed),
+                // Shifting a negative value left is undefined
+                BinaryOperation::LeftShift if !shifted.signed || shifted.value >= 0 => count
+                    .and_then(|count| {
+                        Value::new(shifted.value << count, shifted.bits, shifted.signed)
+                    }),
+                BinaryOperation::RightShift => {
+                    count.map(|count| shifted.with(shifted.value >> count))
+                }
+                BinaryOperation::LessThan => Some(Value::int(a < b)),
+                BinaryOperation::GreaterThan => Some(Value::int(a > b)),
+                BinaryOperation::LessThanEqual => Some(Value::int(a <= b)),
+                BinaryOperation::GreaterThanEqual => Some(Value::int(a >= b)),
+                BinaryOperation::Equal => Some(Value::int(a == b)),
+                BinaryOperation::NotEqual => Some(Value::int(a != b)),
+                BinaryOperation::BitwiseAnd => Value::new(a & b, bits, signed),
+                BinaryOperation::BitwiseOr => Value::new(a | b, bits, signed),
+                BinaryOperation::BitwiseXor => Value::new(a ^ b, bits, signed),
+                BinaryOperation::LogicalAnd => Some(Value::int(l.value != 0 && r.value != 0)),
+                BinaryOperation::LogicalOr => Some(Value::int(l.value != 0 || r.value != 0)),
+                BinaryOperation::Comma => Some(r),
+                _ => None,
+            };
+            value.ok_or_else(fail)
+        }
+        Expression::Ternary {
+            condition,
+            if_true,
+            if_false,
+        } => {
+            let chosen = if eval(condition)?.value != 0 {
+                eval(if_true)?
+            } else {
+                eval(if_false)?
+            };
+            // The result has the common type of both branches when the other one is constant too
+            let (t, f) = match (eval(if_true), eval(if_false)) {
+                (Ok(t), Ok(f)) => (t, f),
+                _ => return Ok(chosen),
+            };
+            let (bits, signed) = common_type(t, f);
+            chosen.convert(bits, signed).ok_or_else(fail)
+        }
+        _ => Err(fail()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attribute::Attribute;
+    use crate::expression::{add, bitwise_neg, cast, identifier, less_than, right_shift, subtract};
+    use crate::file::Include;
+
+    fn number(value: i128) -> Expression {
+        Expression::Literal(Literal::Signed(value))
+    }
+
+    fn mixed() -> Type {
+        TypeBuilder::new()
+            .struct_named("mixed")
+            .unwrap()
+            .with_fields()
+            .named_field(TypeBuilder::new().char(), "tag")
+            .unwrap()
+            .named_field(TypeBuilder::new().long(), "count")
+            .unwrap()
+            .named_field(TypeBuilder::new().double(), "value")
+            .unwrap()
+            .named_field(
+                TypeBuilder::new().sized_array_of(number(3)).short(),
+                "extra",
+            )
+            .unwrap()
+            .finish()
+            .unwrap()
+    }
+
+    #[test]
+    fn struct_layouts() {
+        let offsets = |abi| {
+            let layout = mixed().layout(abi).unwrap();
+            let offsets: Vec<u64> = layout.fields().iter().map(|f| f.offset()).collect();
+            (layout.size(), layout.align(), offsets)
+        };
+        assert_eq!(offsets(Abi::X86_64SysV), (32, 8, vec![0, 8, 16, 24]));
+        assert_eq!(offsets(Abi::WindowsX64), (24, 8, vec![0, 4, 8, 16]));
+        assert_eq!(offsets(Abi::I386), (24, 4, vec![0, 4, 8, 16]));
+        assert_eq!(offsets(Abi::ArmLinuxEabi), (24, 8, vec![0, 4, 8, 16]));
+
+        let pointer = TypeBuilder::new().pointer_to().void();
+        assert_eq!(pointer.layout(Abi::RiscV32).unwrap().size(), 4);
+        assert_eq!(pointer.layout(Abi::AArch64).unwrap().size(), 8);
+        let long_double = TypeBuilder::new().long_double();
+        assert_eq!(long_double.layout(Abi::I386).unwrap().size(), 12);
+        assert_eq!(long_double.layout(Abi::WindowsX64).unwrap().size(), 8);
+        assert!(matches!(
+            TypeBuilder::new().void().layout(Abi::X86_64SysV),
+            Err(Error::IncompleteType(_))
+        ));
+    }
+
+    #[test]
+    fn bit_fields() {
+        let flags = TypeBuilder::new()
+            .anonymous_struct()
+            .named_bit_field(TypeBuilder::new().unsigned_char(), "kind", number(3))
+            .unwrap()
+            .named_bit_field(TypeBuilder::new().unsigned_int(), "length", number(20))
+            .unwrap()
+            .named_bit_field(TypeBuilder::new().unsigned_int(), "extra", number(12))
+            .unwrap()
+            .finish()
+            .unwrap();
+        let bits = |abi| -> Vec<Option<u64>> {
+            let layout = flags.layout(abi).unwrap();
+            let mut bits: Vec<_> = layout.fields().iter().map(|f| f.bit_offset()).collect();
+            bits.push(Some(layout.size()));
+            bits
+        };
+        assert_eq!(
+            bits(Abi::X86_64SysV),
+            vec![Some(0), Some(3), Some(32), Some(8)]
+        );
+        assert_eq!(
+            bits(Abi::WindowsX64),
+            vec![Some(0), Some(32), Some(52), Some(8)]
+        );
+
+        let separated = TypeBuilder::new()
+            .anonymous_struct()
+            .named_bit_field(TypeBuilder::new().int(), "a", number(1))
+            .unwrap()
+            .anonymous_bit_field(TypeBuilder::new().long_long(), number(0))
+            .named_bit_field(TypeBuilder::new().int(), "b", number(1))
+            .unwrap()
+            .finish()
+            .unwrap();
+        let layout = separated.layout(Abi::X86_64SysV).unwrap();
+        assert_eq!(layout.field("b").unwrap().offset(), 8);
+        assert_eq!((layout.size(), layout.align()), (12, 4));
+    }
+
+    #[test]
+    fn attributes() {
+        let wire = |attribute| {
+            TypeBuilder::new()
+                .anonymous_struct()
+                .named_field(TypeBuilder::new().unsigned_char(), "tag")
+                .unwrap()
+                .named_field(TypeBuilder::new().unsigned_long_long(), "length")
+                .unwrap()
+                .named_field(TypeBuilder::new().unsigned_short(), "crc")
+                .unwrap()
+                .field_attribute(Attribute::aligned(4).unwrap())
+                .unwrap()
+                .attribute(attribute)
+                .unwrap()
+                .finish()
+                .unwrap()
+        };
+        let layout = |attribute, abi| {
+            let layout = wire(attribute).layout(abi).unwrap();
+            let offsets: Vec<u64> = layout.fields().iter().map(|f| f.offset()).collect();
+            (layout.size(), layout.align(), offsets)
+        };
+        assert_eq!(
+            layout(Attribute::Packed, Abi::X86_64SysV),
+            (16, 4, vec![0, 1, 12])
+        );
+        assert_eq!(
+            layout(Attribute::aligned(32).unwrap(), Abi::I386),
+            (32, 32, vec![0, 4, 12])
+        );
+    }
+
+    #[test]
+    fn evaluation() {
+        let huge = TypeBuilder::new()
+            .sized_array_of(number(1 << 62))
+            .sized_array_of(number(16))
+            .char();
+        assert!(matches!(
+            huge.layout(Abi::X86_64SysV),
+            Err(Error::TypeTooLarge(_))
+        ));
+        let overflowing = TypeBuilder::new()
+            .anonymous_enum()
+            .enumerator_with_value("A", number(i128::MAX))
+            .unwrap()
+            .enumerator("B")
+            .unwrap()
+            .finish();
+        assert!(matches!(
+            overflowing.layout(Abi::X86_64SysV),
+            Err(Error::NonConstantExpression(_))
+        ));
+
+        let length = |expression, abi| {
+            TypeBuilder::new()
+                .sized_array_of(expression)
+                .char()
+                .layout(abi)
+                .map(|layout| layout.size())
+        };
+        let truncated = cast(number(300), TypeBuilder::new().unsigned_char());
+        assert_eq!(length(truncated, Abi::X86_64SysV).unwrap(), 44);
+        let wrapped = add(
+            cast(
+                literal(Literal::Unsigned(0xffff_ffff)),
+                TypeBuilder::new().int(),
+            ),
+            number(2),
+        );
+        assert_eq!(length(wrapped, Abi::X86_64SysV).unwrap(), 1);
+        let plain = |abi| {
+            let value = cast(number(200), TypeBuilder::new().char());
+            length(add(value, number(100)), abi)
+        };
+        assert!(plain(Abi::X86_64SysV).is_ok_and(|size| size == 44));
+        assert!(plain(Abi::AArch64).is_ok_and(|size| size == 300));
+        let unsigned = |value| literal(Literal::Unsigned(value));
+        let shifted = right_shift(bitwise_neg(unsigned(0)), number(28));
+        assert_eq!(length(shifted, Abi::X86_64SysV).unwrap(), 15);
+        let negated = subtract(unsigned(0), number(1));
+        assert_eq!(length(negated, Abi::X86_64SysV).unwrap(), 0xffff_ffff);
+        // -1 converts to unsigned, so the comparison is false
+        let compared = add(less_than(number(-1), unsigned(1)), number(1));
+        assert_eq!(length(compared, Abi::X86_64SysV).unwrap(), 1);
+        let overflow = add(number(0x7fff_ffff), number(1));
+        assert!(matches!(
+            length(overflow, Abi::X86_64SysV),
+            Err(Error::NonConstantExpression(_))
+        ));
+        let long = || add(literal(Literal::SignedLong(0x7fff_ffff)), number(1));
+        assert_eq!(length(long(), Abi::X86_64SysV).unwrap(), 0x8000_0000);
+        assert!(length(long(), Abi::WindowsX64).is_err());
+        let pointer = cast(number(8), TypeBuilder::new().pointer_to().void());
+        assert!(matches!(
+            length(pointer, Abi::X86_64SysV),
+            Err(Error::NonConstantExpression(_))
+        ));
+    }
+
+    #[test]
+    fn enums() {
+        let small = TypeBuilder::new()
+            .anonymous_enum()
+            .enumerator("A")
+            .unwrap()
+            .enumerator_with_value("B", number(0xffff_ffff))
+            .unwrap()
+            .finish();
+        assert_eq!(small.layout(Abi::X86_64SysV).unwrap().size(), 4);
+        let large = TypeBuilder::new()
+            .anonymous_enum()
+            .enumerator_with_value("A", number(-1))
+            .unwrap()
+            .enumerator_with_value("B", add(identifier("A").unwrap(), number(0x1_0000_0000)))
+            .unwrap()
+            .finish();
+        assert_eq!(large.layout(Abi::X86_64SysV).unwrap().size(), 8);
+        assert_eq!(large.layout(Abi::I386).unwrap().align(), 4);
+        assert_eq!(large.layout(Abi::WindowsX64).unwrap().size(), 4);
+        assert_eq!(large.layout(Abi::ArmNoneEabi).unwrap().size(), 8);
+
+        // Bare-metal Arm picks the smallest type that holds every value
+        let sizes = |enumerator| {
+            let t = TypeBuilder::new()
+                .anonymous_enum()
+                .enumerator_with_value("A", number(enumerator))
+                .unwrap()
+                .finish();
+            (
+                t.layout(Abi::ArmNoneEabi).unwrap().size(),
+                t.layout(Abi::ArmLinuxEabi).unwrap().size(),
+            )
+        };
+        assert_eq!(sizes(255), (1, 4));
+        assert_eq!(sizes(-128), (1, 4));
+        assert_eq!(sizes(256), (2, 4));
+        assert_eq!(sizes(0x1_0000), (4, 4));
+
+        let incomplete = TypeBuilder::new().enum_named("color").unwrap().finish();
+        assert!(matches!(
+            incomplete.layout(Abi::X86_64SysV),
+            Err(Error::IncompleteType(_))
+        ));
+    }
+
+    #[test]
+    fn assertions() {
+        let assertions: Vec<String> = mixed()
+            .layout_assertions(Abi::X86_64SysV)
+            .unwrap()
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert_eq!(
+            assertions,
+            vec![
+                "_Static_assert(sizeof(struct mixed) == 32, \"sizeof(struct mixed) == 32\");",
+                "_Static_assert(_Alignof(struct mixed) == 8, \"_Alignof(struct mixed) == 8\");",
+                "_Static_assert(offsetof(struct mixed, tag) == 0, \"offsetof(struct mixed, tag) == 0\");",
+                "_Static_assert(offsetof(struct mixed, count) == 8, \"offsetof(struct mixed, count) == 8\");",
+                "_Static_assert(offsetof(struct mixed, value) == 16, \"offsetof(struct mixed, value) == 16\");",
+                "_Static_assert(offsetof(struct mixed, extra) == 24, \"offsetof(struct mixed, extra) == 24\");",
+            ]
+        );
+        let includes = mixed().layout_assertions(Abi::I386).unwrap()[2].required_includes();
+        assert_eq!(includes, vec![Include::bracket("stddef.h")]);
+        assert!(matches!(
+            TypeBuilder::new()
+                .anonymous_union()
+                .finish()
+                .unwrap()
+                .layout_assertions(Abi::I386),
+            Err(Error::UnnamedType(_))
+        ));
+    }
+}
@@ -1,14 +1,26 @@
-//mod declaration;
-mod expression;
-//mod file;
+mod attribute;
+mod declaration;
 mod error;
+mod expression;
+mod file;
 mod identifier;
+mod initializer;
+mod layout;
 mod literal;
+mod preprocessor;
+mod standard;
+mod statement;
 mod r#type;
-//pub use declaration::*;
-pub use expression::*;
-//pub use file::*;
+pub use attribute::*;
+pub use declaration::*;
 pub use error::*;
+pub use expression::*;
+pub use file::*;
 pub use identifier::*;
+pub use initializer::*;
+pub use layout::*;
 pub use literal::*;
+pub use preprocessor::*;
 pub use r#type::*;
+pub use standard::*;
+pub use statement::*;
@@ -1,3 +1,6 @@
+use crate::error::{Error, Result};
+use crate::standard::Standard;
+
 #[derive(Debug)]
 pub enum Literal {
     Signed(i128),
@@ -6,13 +9,82 @@ pub enum Literal {
     Unsigned(u128),
     UnsignedLong(u128),
     UnsignedLongLong(u128),
+    Integer(IntegerConstant),
     Character(char),
     WideCharacter(char),
+    Utf8Character(char),
+    Utf16Character(char),
+    Utf32Character(char),
     Float(FloatConstant),
     Double(FloatConstant),
     LongDouble(FloatConstant),
     String(String),
     WideString(Vec<char>),
+    Utf8String(String),
+    Utf16String(String),
+    Utf32String(String),
+}
+
+impl Literal {
+    pub fn character(value: char) -> Result<Self> {
+        Encoding::Narrow.check(value)?;
+        Ok(Literal::Character(value))
+    }
+
+    pub fn utf8_character(value: char) -> Result<Self> {
+        Encoding::Utf8.check(value)?;
+        Ok(Literal::Utf8Character(value))
+    }
+
+    pub fn utf16_character(value: char) -> Result<Self> {
+        Encoding::Utf16.check(value)?;
+        Ok(Literal::Utf16Character(value))
+    }
+
+    pub fn utf32_character(value: char) -> Self {
+        Literal::Utf32Character(value)
+    }
+
+    // Rewrite or reject spellings that the target standard lacks
+    pub(crate) fn lower(&mut self, standard: Standard) -> Result<()> {
+        // The variants can be built directly, bypassing the constructors' checks
+        match self {
+            Literal::Character(value) => Encoding::Narrow.check(*value)?,
+            Literal::Utf8Character(value) => Encoding::Utf8.check(*value)?,
+            Literal::Utf16Character(value) => Encoding::Utf16.check(*value)?,
+            // A leading minus is a separate operator, so only the magnitude is a constant
+            Literal::Signed(value)
+            | Literal::SignedLong(value)
+            | Literal::SignedLongLong(value) => check_range(value.unsigned_abs(), true)?,
+            Literal::Unsigned(value)
+            | Literal::UnsignedLong(value)
+            | Literal::UnsignedLongLong(value) => check_range(*value, false)?,
+            _ => {}
+        }
+        match self {
+            Literal::SignedLongLong(_) | Literal::UnsignedLongLong(_) => {
+                standard.require(Standard::C99, true, "long long")
+            }
+            Literal::Integer(constant) => constant.lower(standard),
+            Literal::Float(constant)
+            | Literal::Double(constant)
+            | Literal::LongDouble(constant)
+                if constant.is_hexadecimal() =>
+            {
+                standard.require(Standard::C99, true, "hexadecimal floating constants")
+            }
+            Literal::Utf8Character(_) => {
+                standard.require(Standard::C23, false, "u8 character constants")
+            }
+            Literal::Utf16Character(_) | Literal::Utf32Character(_) => {
+                standard.require(Standard::C11, false, "u and U character constants")
+            }
+            Literal::Utf8String(_) | Literal::Utf16String(_) | Literal::Utf32String(_) => {
+                standard.require(Standard::C11, false, "unicode string literals")
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl std::fmt::Display for Literal {
@@ -24,21 +96,113 @@ impl std::fmt::Display for Literal {
             Literal::Unsigned(val) => write!(f, "{}U", val),
             Literal::UnsignedLong(val) => write!(f, "{}UL", val),
             Literal::UnsignedLongLong(val) => write!(f, "{}ULL", val),
-            Literal::Character(val) => write!(f, "'{}'", val.escape_default()),
-            Literal::WideCharacter(val) => write!(f, "L'{}'", val.escape_default()),
+            Literal::Integer(val) => write!(f, "{}", val),
+            Literal::Character(val) => f.write_str(&quote(Encoding::Narrow, '\'', &[*val])),
+            Literal::WideCharacter(val) => f.write_str(&quote(Encoding::Wide, '\'', &[*val])),
+            Literal::Utf8Character(val) => f.write_str(&quote(Encoding::Utf8, '\'', &[*val])),
+            Literal::Utf16Character(val) => f.write_str(&quote(Encoding::Utf16, '\'', &[*val])),
+            Literal::Utf32Character(val) => f.write_str(&quote(Encoding::Utf32, '\'', &[*val])),
             Literal::Float(val) => write!(f, "{}f", val),
             Literal::Double(val) => write!(f, "{}", val),
             Literal::LongDouble(val) => write!(f, "{}L", val),
-            Literal::String(val) => write!(f, "\"{}\"", val.as_str().escape_default()),
-            Literal::WideString(val) => write!(
-                f,
-                "L\"{}\"",
-                val.iter()
-                    .map(|x| x.escape_default().to_string())
-                    .collect::<String>()
-            ),
+            Literal::String(val) => f.write_str(&quote_str(Encoding::Narrow, val)),
+            Literal::WideString(val) => f.write_str(&quote(Encoding::Wide, '"', val)),
+            Literal::Utf8String(val) => f.write_str(&quote_str(Encoding::Utf8, val)),
+            Literal::Utf16String(val) => f.write_str(&quote_str(Encoding::Utf16, val)),
+            Literal::Utf32String(val) => f.write_str(&quote_str(Encoding::Utf32, val)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Narrow,
+    Wide,
+    Utf8,
+    Utf16,
+    Utf32,
+}
+
+impl Encoding {
+    fn prefix(&self) -> &'static str {
+        match self {
+            Encoding::Narrow => "",
+            Encoding::Wide => "L",
+            Encoding::Utf8 => "u8",
+            Encoding::Utf16 => "u",
+            Encoding::Utf32 => "U",
+        }
+    }
+
+    // Whether code units are bytes, rather than holding the code point directly
+    fn is_byte_oriented(&self) -> bool {
+        matches!(self, Encoding::Narrow | Encoding::Utf8)
+    }
+
+    // Check that a character constant fits in a single code unit
+    fn check(&self, value: char) -> Result<()> {
+        let fits = match self {
+            Encoding::Narrow | Encoding::Utf8 => value.is_ascii(),
+            Encoding::Utf16 => value.len_utf16() == 1,
+            Encoding::Wide | Encoding::Utf32 => true,
+        };
+        if fits {
+            Ok(())
+        } else {
+            Err(Error::UnrepresentableCharacter(value))
+        }
+    }
+}
+
+fn quote_str(encoding: Encoding, text: &str) -> String {
+    quote(encoding, '"', &text.chars().collect::<Vec<_>>())
+}
+
+// Render `text` as one or more adjacent C literals, escaping anything that isn't plain ASCII.
+// Hex escapes are greedy, so a literal is split if a hex digit would follow one.
+fn quote(encoding: Encoding, quote: char, text: &[char]) -> String {
+    let prefix = encoding.prefix();
+    let mut s = format!("{}{}", prefix, quote);
+    let mut after_hex = false;
+    for (i, c) in text.iter().copied().enumerate() {
+        if after_hex && c.is_ascii_hexdigit() {
+            s.push_str(&format!("{} {}{}", quote, prefix, quote));
+        }
+        after_hex = false;
+        match c {
+            '\\' => s.push_str("\\\\"),
+            '\u{7}' => s.push_str("\\a"),
+            '\u{8}' => s.push_str("\\b"),
+            '\u{c}' => s.push_str("\\f"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            '\u{b}' => s.push_str("\\v"),
+            // Avoid forming trigraphs
+            '?' if i > 0 && text[i - 1] == '?' => s.push_str("\\?"),
+            c if c == quote => {
+                s.push('\\');
+                s.push(c);
+            }
+            ' '..='~' => s.push(c),
+            // Universal character names can't designate C0 or C1 control characters
+            '\u{0}'..='\u{9f}' => {
+                if !encoding.is_byte_oriented() || c.is_ascii() {
+                    s.push_str(&format!("\\x{:x}", c as u32));
+                } else {
+                    let mut buffer = [0; 4];
+                    for byte in c.encode_utf8(&mut buffer).bytes() {
+                        s.push_str(&format!("\\x{:x}", byte));
+                    }
+                }
+                after_hex = true;
+            }
+            '\u{a0}'..='\u{ffff}' => s.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => s.push_str(&format!("\\U{:08x}", c as u32)),
         }
     }
+    s.push(quote);
+    s
 }
 
 #[derive(Debug)]
@@ -53,21 +217,160 @@ impl FloatConstant {
         }
     }
 
+    // The fraction is written as an integer, so it can't carry leading zeros and `1.05` has no
+    // spelling
+    #[deprecated(note = "use `FloatConstant::parse`, which accepts any fraction")]
     pub fn decimal(integer: i128, fraction: i128) -> Self {
         Self {
             value: format!("{}.{}", integer, fraction),
         }
     }
 
+    #[deprecated(note = "use `FloatConstant::parse`, which accepts any fraction")]
     pub fn scientific(integer: i128, fraction: i128, exponent: i128) -> Self {
         Self {
             value: format!("{}.{}e{}", integer, fraction, exponent),
         }
     }
 
-    pub fn from_float(float: f64) -> Self {
-        Self {
-            value: format!("{:.}", float),
+    // Infinities and NaN have no constant spelling; see `double_value` for those
+    pub fn from_float(float: f64) -> Result<Self> {
+        Self::from_f64(float)
+    }
+
+    // Parse a decimal (`1.05`, `2e-3`) or hexadecimal (`0x1.8p3`) floating constant, without suffix
+    pub fn parse<S: Into<String>>(value: S) -> Result<Self> {
+        let value = value.into();
+        if is_floating_constant(&value) {
+            Ok(Self { value })
+        } else {
+            Err(Error::InvalidFloat(value))
+        }
+    }
+
+    fn is_hexadecimal(&self) -> bool {
+        let value = self.value.trim_start_matches('-');
+        value.starts_with("0x") || value.starts_with("0X")
+    }
+
+    // The shortest decimal representation that converts back to exactly the same value
+    pub fn from_f64(float: f64) -> Result<Self> {
+        if !float.is_finite() {
+            return Err(Error::InvalidFloat(float.to_string()));
+        }
+        Ok(Self {
+            value: format!("{:?}", float),
+        })
+    }
+
+    pub fn from_f32(float: f32) -> Result<Self> {
+        if !float.is_finite() {
+            return Err(Error::InvalidFloat(float.to_string()));
+        }
+        Ok(Self {
+            value: format!("{:?}", float),
+        })
+    }
+
+    pub fn hexadecimal_from_f64(float: f64) -> Result<Self> {
+        if !float.is_finite() {
+            return Err(Error::InvalidFloat(float.to_string()));
+        }
+        let bits = float.to_bits();
+        Ok(Self {
+            value: hexadecimal(
+                float.is_sign_negative(),
+                ((bits >> 52) & 0x7ff) as i32,
+                bits & ((1 << 52) - 1),
+                52,
+                1023,
+            ),
+        })
+    }
+
+    pub fn hexadecimal_from_f32(float: f32) -> Result<Self> {
+        if !float.is_finite() {
+            return Err(Error::InvalidFloat(float.to_string()));
+        }
+        let bits = float.to_bits();
+        Ok(Self {
+            value: hexadecimal(
+                float.is_sign_negative(),
+                ((bits >> 23) & 0xff) as i32,
+                (bits & ((1 << 23) - 1)) as u64,
+                23,
+                127,
+            ),
+        })
+    }
+}
+
+// Format an IEEE 754 value exactly as a C99 hexadecimal floating constant
+fn hexadecimal(
+    negative: bool,
+    exponent: i32,
+    mantissa: u64,
+    mantissa_bits: u32,
+    bias: i32,
+) -> String {
+    let sign = if negative { "-" } else { "" };
+    if exponent == 0 && mantissa == 0 {
+        return format!("{}0x0p0", sign);
+    }
+    let (leading, exponent) = if exponent == 0 {
+        (0, 1 - bias)
+    } else {
+        (1, exponent - bias)
+    };
+    // Pad the mantissa out to a whole number of hex digits
+    let padding = (4 - mantissa_bits % 4) % 4;
+    let digits = ((mantissa_bits + padding) / 4) as usize;
+    let fraction = format!("{:0width$x}", mantissa << padding, width = digits);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{}0x{}p{}", sign, leading, exponent)
+    } else {
+        format!("{}0x{}.{}p{}", sign, leading, fraction, exponent)
+    }
+}
+
+fn is_floating_constant(value: &str) -> bool {
+    let value = value.strip_prefix('-').unwrap_or(value);
+    let digits = |s: &str, hex: bool| {
+        s.chars().all(|c| {
+            if hex {
+                c.is_ascii_hexdigit()
+            } else {
+                c.is_ascii_digit()
+            }
+        })
+    };
+    let exponent = |s: &str| {
+        let s = s.strip_prefix(['+', '-']).unwrap_or(s);
+        !s.is_empty() && digits(s, false)
+    };
+    let significand = |s: &str, hex: bool| match s.split_once('.') {
+        Some((whole, fraction)) => {
+            !(whole.is_empty() && fraction.is_empty())
+                && digits(whole, hex)
+                && digits(fraction, hex)
+        }
+        None => !s.is_empty() && digits(s, hex),
+    };
+    if let Some(hex) = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        // Hexadecimal constants require a binary exponent
+        match hex.split_once(['p', 'P']) {
+            Some((mantissa, power)) => significand(mantissa, true) && exponent(power),
+            None => false,
+        }
+    } else {
+        // Decimal constants require either a decimal point or an exponent
+        match value.split_once(['e', 'E']) {
+            Some((mantissa, power)) => significand(mantissa, false) && exponent(power),
+            None => value.contains('.') && significand(value, false),
         }
     }
 }
@@ -77,3 +380,386 @@ impl std::fmt::Display for FloatConstant {
         f.write_str(&self.value)
     }
 }
+
+// Widths of `int`, `long` and `long long`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataModel {
+    LP32,
+    ILP32,
+    LLP64,
+    LP64,
+    ILP64,
+}
+
+impl DataModel {
+    fn widths(&self) -> [u32; 3] {
+        match self {
+            DataModel::LP32 => [16, 32, 64],
+            DataModel::ILP32 | DataModel::LLP64 => [32, 32, 64],
+            DataModel::LP64 => [32, 64, 64],
+            DataModel::ILP64 => [64, 64, 64],
+        }
+    }
+
+    // Index of the smallest of `int`, `long` and `long long` that holds `value`
+    fn rank(&self, value: u128, signed: bool) -> Option<usize> {
+        self.widths().iter().position(|width| {
+            let bits = if signed { width - 1 } else { *width };
+            value >> bits == 0
+        })
+    }
+}
+
+// Every integer constant must fit in `long long` or `unsigned long long`, which are 64 bits on
+// all supported data models
+fn check_range(value: u128, signed: bool) -> Result<()> {
+    let bits = if signed { 63 } else { 64 };
+    if value >> bits == 0 {
+        Ok(())
+    } else {
+        Err(Error::IntegerOutOfRange(value.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radix {
+    Binary,
+    Octal,
+    Decimal,
+    Hexadecimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerSuffix {
+    None,
+    Unsigned,
+    Long,
+    UnsignedLong,
+    LongLong,
+    UnsignedLongLong,
+}
+
+impl std::fmt::Display for IntegerSuffix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            IntegerSuffix::None => "",
+            IntegerSuffix::Unsigned => "U",
+            IntegerSuffix::Long => "L",
+            IntegerSuffix::UnsignedLong => "UL",
+            IntegerSuffix::LongLong => "LL",
+            IntegerSuffix::UnsignedLongLong => "ULL",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntegerConstant {
+    value: u128,
+    suffix: IntegerSuffix,
+    radix: Radix,
+    separator: Option<usize>,
+}
+
+impl IntegerConstant {
+    pub fn new(value: u128, suffix: IntegerSuffix) -> Self {
+        Self {
+            value,
+            suffix,
+            radix: Radix::Decimal,
+            separator: None,
+        }
+    }
+
+    // Choose the smallest of `int`, `long` and `long long` that can represent `value`
+    pub fn signed(value: u128, model: DataModel) -> Result<Self> {
+        const SUFFIXES: [IntegerSuffix; 3] = [
+            IntegerSuffix::None,
+            IntegerSuffix::Long,
+            IntegerSuffix::LongLong,
+        ];
+        match model.rank(value, true) {
+            Some(rank) => Ok(Self::new(value, SUFFIXES[rank])),
+            None => Err(Error::IntegerOutOfRange(value.to_string())),
+        }
+    }
+
+    // Choose the smallest of the unsigned integer types that can represent `value`
+    pub fn unsigned(value: u128, model: DataModel) -> Result<Self> {
+        const SUFFIXES: [IntegerSuffix; 3] = [
+            IntegerSuffix::Unsigned,
+            IntegerSuffix::UnsignedLong,
+            IntegerSuffix::UnsignedLongLong,
+        ];
+        match model.rank(value, false) {
+            Some(rank) => Ok(Self::new(value, SUFFIXES[rank])),
+            None => Err(Error::IntegerOutOfRange(value.to_string())),
+        }
+    }
+
+    pub(crate) fn value(&self) -> u128 {
+        self.value
+    }
+
+    pub fn suffix(&self) -> IntegerSuffix {
+        self.suffix
+    }
+
+    pub(crate) fn is_decimal(&self) -> bool {
+        self.radix == Radix::Decimal
+    }
+
+    pub(crate) fn lower(&mut self, standard: Standard) -> Result<()> {
+        // Octal and hexadecimal constants without a `U` suffix may still become unsigned
+        let signed = self.is_decimal()
+            && matches!(
+                self.suffix,
+                IntegerSuffix::None | IntegerSuffix::Long | IntegerSuffix::LongLong
+            );
+        check_range(self.value, signed)?;
+        if let IntegerSuffix::LongLong | IntegerSuffix::UnsignedLongLong = self.suffix {
+            standard.require(Standard::C99, true, "long long")?;
+        }
+        // Binary constants are a GNU extension before C23, but hexadecimal is always available
+        if self.radix == Radix::Binary && !standard.at_least(Standard::C23) && !standard.is_gnu() {
+            self.radix = Radix::Hexadecimal;
+        }
+        if !standard.at_least(Standard::C23) {
+            self.separator = None;
+        }
+        Ok(())
+    }
+
+    pub fn radix(mut self, radix: Radix) -> Self {
+        self.radix = radix;
+        self
+    }
+
+    // Group digits with C23 digit separators, counting from the least significant digit
+    pub fn digit_separators(mut self, group: usize) -> Self {
+        self.separator = if group > 0 { Some(group) } else { None };
+        self
+    }
+}
+
+impl std::fmt::Display for IntegerConstant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (prefix, digits) = match self.radix {
+            Radix::Binary => ("0b", format!("{:b}", self.value)),
+            Radix::Octal if self.value == 0 => ("", "0".to_string()),
+            Radix::Octal => ("0", format!("{:o}", self.value)),
+            Radix::Decimal => ("", self.value.to_string()),
+            Radix::Hexadecimal => ("0x", format!("{:X}", self.value)),
+        };
+        f.write_str(prefix)?;
+        if let Some(group) = self.separator {
+            for (i, digit) in digits.chars().enumerate() {
+                if i > 0 && (digits.len() - i) % group == 0 {
+                    f.write_str("'")?;
+                }
+                write!(f, "{}", digit)?;
+            }
+        } else {
+            f.write_str(&digits)?;
+        }
+        write!(f, "{}", self.suffix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple_escapes() {
+        let l = Literal::String("tab\there \"quoted\" back\\slash\n".to_string());
+        assert_eq!(
+            format!("{}", l),
+            "\"tab\\there \\\"quoted\\\" back\\\\slash\\n\""
+        );
+        assert_eq!(format!("{}", Literal::Character('\'')), "'\\''");
+        assert_eq!(format!("{}", Literal::Character('"')), "'\"'");
+        assert_eq!(
+            format!("{}", Literal::String("??=".to_string())),
+            "\"?\\?=\""
+        );
+    }
+
+    #[test]
+    fn universal_character_names() {
+        assert_eq!(
+            format!("{}", Literal::String("caf\u{e9} \u{1F600}".to_string())),
+            "\"caf\\u00e9 \\U0001f600\""
+        );
+        assert_eq!(
+            format!("{}", Literal::WideCharacter('\u{e9}')),
+            "L'\\u00e9'"
+        );
+    }
+
+    #[test]
+    fn prefixes() {
+        assert_eq!(
+            format!("{}", Literal::Utf8String("\u{e9}\u{85}".to_string())),
+            "u8\"\\u00e9\\xc2\\x85\""
+        );
+        assert_eq!(
+            format!("{}", Literal::Utf16String("\u{85}a\u{1F600}".to_string())),
+            "u\"\\x85\" u\"a\\U0001f600\""
+        );
+        assert_eq!(
+            format!("{}", Literal::Utf32String("\u{1F600}".to_string())),
+            "U\"\\U0001f600\""
+        );
+        assert_eq!(
+            format!("{}", Literal::utf8_character('x').unwrap()),
+            "u8'x'"
+        );
+        assert_eq!(
+            format!("{}", Literal::utf16_character('\u{e9}').unwrap()),
+            "u'\\u00e9'"
+        );
+        assert_eq!(
+            format!("{}", Literal::utf32_character('\u{1F600}')),
+            "U'\\U0001f600'"
+        );
+    }
+
+    #[test]
+    fn unrepresentable_characters() {
+        Literal::character('\u{e9}').unwrap_err();
+        Literal::utf8_character('\u{e9}').unwrap_err();
+        Literal::utf16_character('\u{1F600}').unwrap_err();
+        Literal::character('a').unwrap();
+        Literal::Utf16Character('\u{1F600}')
+            .lower(Standard::C23)
+            .unwrap_err();
+        Literal::Character('\u{e9}')
+            .lower(Standard::C23)
+            .unwrap_err();
+        Literal::Utf16Character('\u{e9}')
+            .lower(Standard::C23)
+            .unwrap();
+    }
+
+    #[test]
+    fn hex_escapes() {
+        assert_eq!(
+            format!("{}", Literal::String("\u{0}1\u{7f}ab\u{1b}g".to_string())),
+            "\"\\x0\" \"1\\x7f\" \"ab\\x1bg\""
+        );
+        assert_eq!(
+            format!("{}", Literal::String("\u{85}".to_string())),
+            "\"\\xc2\\x85\""
+        );
+        assert_eq!(
+            format!("{}", Literal::WideString(vec!['\u{85}', 'f'])),
+            "L\"\\x85\" L\"f\""
+        );
+    }
+
+    #[test]
+    fn integer_suffixes() {
+        let c = IntegerConstant::signed(2_147_483_647, DataModel::LP64).unwrap();
+        assert_eq!(format!("{}", c), "2147483647");
+        let c = IntegerConstant::signed(2_147_483_648, DataModel::LP64).unwrap();
+        assert_eq!(format!("{}", c), "2147483648L");
+        let c = IntegerConstant::signed(2_147_483_648, DataModel::LLP64).unwrap();
+        assert_eq!(format!("{}", c), "2147483648LL");
+        let c = IntegerConstant::unsigned(0xDEAD_BEEF, DataModel::ILP32)
+            .unwrap()
+            .radix(Radix::Hexadecimal);
+        assert_eq!(format!("{}", c), "0xDEADBEEFU");
+        let c = IntegerConstant::unsigned(u64::MAX as u128, DataModel::LP64).unwrap();
+        assert_eq!(format!("{}", c), "18446744073709551615UL");
+        IntegerConstant::signed(i64::MAX as u128 + 1, DataModel::LP64).unwrap_err();
+        IntegerConstant::unsigned(u64::MAX as u128 + 1, DataModel::LP64).unwrap_err();
+    }
+
+    #[test]
+    fn integer_ranges() {
+        let lower = |mut literal: Literal| literal.lower(Standard::C23);
+        assert!(matches!(
+            lower(Literal::Signed(i128::MAX)),
+            Err(Error::IntegerOutOfRange(_))
+        ));
+        lower(Literal::Signed(i64::MAX as i128)).unwrap();
+        lower(Literal::Signed(-(i64::MAX as i128))).unwrap();
+        lower(Literal::SignedLong(i64::MIN as i128)).unwrap_err();
+        lower(Literal::UnsignedLongLong(u64::MAX as u128)).unwrap();
+        lower(Literal::Unsigned(u64::MAX as u128 + 1)).unwrap_err();
+        let hex =
+            IntegerConstant::new(u64::MAX as u128, IntegerSuffix::None).radix(Radix::Hexadecimal);
+        lower(Literal::Integer(hex)).unwrap();
+        let decimal = IntegerConstant::new(u64::MAX as u128, IntegerSuffix::None);
+        lower(Literal::Integer(decimal)).unwrap_err();
+    }
+
+    #[test]
+    fn integer_radix() {
+        let c = IntegerConstant::new(8, IntegerSuffix::None);
+        assert_eq!(format!("{}", c.radix(Radix::Octal)), "010");
+        assert_eq!(format!("{}", c.radix(Radix::Binary)), "0b1000");
+        let c = IntegerConstant::new(0, IntegerSuffix::Unsigned).radix(Radix::Octal);
+        assert_eq!(format!("{}", c), "0U");
+        let c = IntegerConstant::new(1_000_000, IntegerSuffix::None).digit_separators(3);
+        assert_eq!(format!("{}", c), "1'000'000");
+        let c = IntegerConstant::new(0xDEAD_BEEF, IntegerSuffix::UnsignedLong)
+            .radix(Radix::Hexadecimal)
+            .digit_separators(4);
+        assert_eq!(format!("{}", c), "0xDEAD'BEEFUL");
+    }
+
+    #[test]
+    fn exact_floats() {
+        assert_eq!(format!("{}", FloatConstant::from_f64(0.1).unwrap()), "0.1");
+        assert_eq!(format!("{}", FloatConstant::from_f64(1.0).unwrap()), "1.0");
+        assert_eq!(
+            format!("{}", FloatConstant::from_f64(1e300).unwrap()),
+            "1e300"
+        );
+        assert_eq!(
+            format!("{}", Literal::Float(FloatConstant::from_f32(0.1).unwrap())),
+            "0.1f"
+        );
+        for value in [0.1, 1.0 / 3.0, f64::MIN_POSITIVE, 5e-324, -2.5e-7, f64::MAX] {
+            let s = FloatConstant::from_f64(value).unwrap().to_string();
+            assert_eq!(s.parse::<f64>().unwrap().to_bits(), value.to_bits());
+        }
+        FloatConstant::from_f64(f64::NAN).unwrap_err();
+        FloatConstant::from_f32(f32::INFINITY).unwrap_err();
+        assert_eq!(
+            format!("{}", FloatConstant::from_float(2.5).unwrap()),
+            "2.5"
+        );
+        FloatConstant::from_float(f64::INFINITY).unwrap_err();
+        FloatConstant::from_float(f64::NAN).unwrap_err();
+    }
+
+    #[test]
+    fn hexadecimal_floats() {
+        let hex = |v| FloatConstant::hexadecimal_from_f64(v).unwrap().to_string();
+        assert_eq!(hex(12.0), "0x1.8p3");
+        assert_eq!(hex(1.0), "0x1p0");
+        assert_eq!(hex(-0.0), "-0x0p0");
+        assert_eq!(hex(0.1), "0x1.999999999999ap-4");
+        assert_eq!(hex(5e-324), "0x0.0000000000001p-1022");
+        let hex = |v| FloatConstant::hexadecimal_from_f32(v).unwrap().to_string();
+        assert_eq!(hex(0.1), "0x1.99999ap-4");
+        assert_eq!(hex(f32::MAX), "0x1.fffffep127");
+    }
+
+    #[test]
+    fn parse_floats() {
+        assert_eq!(format!("{}", FloatConstant::parse("1.05").unwrap()), "1.05");
+        FloatConstant::parse(".5").unwrap();
+        FloatConstant::parse("5.").unwrap();
+        FloatConstant::parse("2e-3").unwrap();
+        FloatConstant::parse("0x1.8p3").unwrap();
+        FloatConstant::parse("0X.8P-1").unwrap();
+        FloatConstant::parse("5").unwrap_err();
+        FloatConstant::parse(".").unwrap_err();
+        FloatConstant::parse("0x1.8").unwrap_err();
+        FloatConstant::parse("1e").unwrap_err();
+        FloatConstant::parse("inf").unwrap_err();
+    }
+}
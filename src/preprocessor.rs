@@ -0,0 +1,291 @@
+use crate::attribute::Dialect;
+use crate::error::{Error, Result};
+use crate::expression::{Expression, Precedence};
+use crate::identifier::Identifier;
+use crate::standard::Standard;
+
+#[derive(Debug)]
+pub enum MacroBody {
+    Expression(Expression),
+    Tokens(String),
+}
+
+impl std::fmt::Display for MacroBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            // Parenthesize compound expressions so the expansion can't be split by its context
+            MacroBody::Expression(expression) => {
+                f.write_str(&expression.render_operand(Precedence::Primary))
+            }
+            MacroBody::Tokens(tokens) => f.write_str(tokens),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Macro {
+    name: Identifier,
+    parameters: Option<Vec<Identifier>>,
+    variadic: bool,
+    body: Option<MacroBody>,
+}
+
+impl Macro {
+    pub(crate) fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        if self.variadic {
+            standard.require(Standard::C99, true, "variadic macros")?;
+        }
+        match &mut self.body {
+            Some(MacroBody::Expression(expression)) => expression.lower(standard, dialect),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Macro {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#define {}", self.name)?;
+        if let Some(parameters) = &self.parameters {
+            let mut list = parameters.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+            if self.variadic {
+                list.push("...".to_string());
+            }
+            write!(f, "({})", list.join(", "))?;
+        }
+        if let Some(body) = &self.body {
+            let body = body.to_string();
+            let lines = body.lines().map(|l| l.trim_end()).collect::<Vec<_>>();
+            if lines.len() > 1 {
+                // Continue each line of a multi-line body with a trailing backslash
+                for line in lines {
+                    write!(f, " \\\n    {}", line)?;
+                }
+            } else if !body.trim().is_empty() {
+                write!(f, " {}", body.trim())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct MacroBuilder {
+    name: Identifier,
+    parameters: Option<Vec<Identifier>>,
+    variadic: bool,
+    body: Option<MacroBody>,
+}
+
+impl MacroBuilder {
+    pub fn object_like<S: Into<String>>(name: S) -> Result<Self> {
+        Ok(Self {
+            name: Identifier::macro_name(name)?,
+            parameters: None,
+            variadic: false,
+            body: None,
+        })
+    }
+
+    pub fn function_like<S: Into<String>>(name: S) -> Result<Self> {
+        Ok(Self {
+            name: Identifier::macro_name(name)?,
+            parameters: Some(Vec::new()),
+            variadic: false,
+            body: None,
+        })
+    }
+
+    pub fn parameter<S: Into<String>>(mut self, name: S) -> Result<Self> {
+        let name = Identifier::macro_name(name)?;
+        let parameters = match &mut self.parameters {
+            Some(parameters) => parameters,
+            None => return Err(Error::ObjectLikeMacroParameter(self.name.to_string())),
+        };
+        if name.to_string() == "__VA_ARGS__" || parameters.contains(&name) {
+            return Err(Error::DuplicateParameter(name.to_string()));
+        }
+        parameters.push(name);
+        Ok(self)
+    }
+
+    pub fn variadic(mut self) -> Result<Self> {
+        if self.parameters.is_none() {
+            return Err(Error::ObjectLikeMacroParameter(self.name.to_string()));
+        }
+        self.variadic = true;
+        Ok(self)
+    }
+
+    pub fn expression(mut self, expression: Expression) -> Self {
+        self.body = Some(MacroBody::Expression(expression));
+        self
+    }
+
+    pub fn tokens<S: Into<String>>(mut self, tokens: S) -> Self {
+        self.body = Some(MacroBody::Tokens(tokens.into()));
+        self
+    }
+
+    pub fn finish(self) -> Macro {
+        Macro {
+            name: self.name,
+            parameters: self.parameters,
+            variadic: self.variadic,
+            body: self.body,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Directive {
+    Define(Macro),
+    Undef(Identifier),
+}
+
+impl Directive {
+    pub fn undef<S: Into<String>>(name: S) -> Result<Self> {
+        Ok(Directive::Undef(Identifier::macro_name(name)?))
+    }
+
+    pub(crate) fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        match self {
+            Directive::Define(definition) => definition.lower(standard, dialect),
+            Directive::Undef(_) => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Directive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Directive::Define(definition) => write!(f, "{}", definition),
+            Directive::Undef(name) => write!(f, "#undef {}", name),
+        }
+    }
+}
+
+impl From<Macro> for Directive {
+    fn from(definition: Macro) -> Self {
+        Directive::Define(definition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::{identifier, left_shift, literal};
+    use crate::literal::Literal;
+
+    #[test]
+    fn object_like() {
+        let m = MacroBuilder::object_like("VERSION")
+            .unwrap()
+            .expression(literal(Literal::Signed(3)))
+            .finish();
+        assert_eq!(format!("{}", m), "#define VERSION 3");
+
+        let m = MacroBuilder::object_like("FLAG")
+            .unwrap()
+            .expression(left_shift(
+                literal(Literal::Signed(1)),
+                literal(Literal::Signed(4)),
+            ))
+            .finish();
+        assert_eq!(format!("{}", m), "#define FLAG (1 << 4)");
+
+        let m = MacroBuilder::object_like("EMPTY").unwrap().finish();
+        assert_eq!(format!("{}", m), "#define EMPTY");
+
+        let m = MacroBuilder::object_like("BLANK")
+            .unwrap()
+            .tokens("   ")
+            .finish();
+        assert_eq!(format!("{}", m), "#define BLANK");
+    }
+
+    #[test]
+    fn function_like() {
+        let m = MacroBuilder::function_like("LOG")
+            .unwrap()
+            .parameter("level")
+            .unwrap()
+            .variadic()
+            .unwrap()
+            .tokens("log_message(level, __VA_ARGS__)")
+            .finish();
+        assert_eq!(
+            format!("{}", m),
+            "#define LOG(level, ...) log_message(level, __VA_ARGS__)"
+        );
+
+        let m = MacroBuilder::function_like("NOW")
+            .unwrap()
+            .expression(identifier("now").unwrap())
+            .finish();
+        assert_eq!(format!("{}", m), "#define NOW() now");
+    }
+
+    #[test]
+    fn multi_line() {
+        let m = MacroBuilder::function_like("SWAP")
+            .unwrap()
+            .parameter("a")
+            .unwrap()
+            .parameter("b")
+            .unwrap()
+            .tokens("do {\n    int t = a;  \n    a = b;\n    b = t;\n} while (0)")
+            .finish();
+        assert_eq!(
+            format!("{}", m),
+            "#define SWAP(a, b) \\\n    do { \\\n        int t = a; \\\n        a = b; \\\n        b = t; \\\n    } while (0)"
+        );
+    }
+
+    #[test]
+    fn bad_parameters() {
+        MacroBuilder::object_like("FOO")
+            .unwrap()
+            .parameter("x")
+            .unwrap_err();
+        MacroBuilder::function_like("FOO")
+            .unwrap()
+            .parameter("x")
+            .unwrap()
+            .parameter("x")
+            .unwrap_err();
+        MacroBuilder::function_like("FOO")
+            .unwrap()
+            .parameter("__VA_ARGS__")
+            .unwrap_err();
+        MacroBuilder::object_like("1FOO").unwrap_err();
+    }
+
+    #[test]
+    fn undef() {
+        assert_eq!(
+            format!("{}", Directive::undef("FOO").unwrap()),
+            "#undef FOO"
+        );
+        assert_eq!(
+            format!("{}", Directive::undef("alignas").unwrap()),
+            "#undef alignas"
+        );
+    }
+
+    #[test]
+    fn keyword_names() {
+        let m = MacroBuilder::object_like("bool")
+            .unwrap()
+            .tokens("_Bool")
+            .finish();
+        assert_eq!(format!("{}", m), "#define bool _Bool");
+
+        let m = MacroBuilder::function_like("noreturn")
+            .unwrap()
+            .parameter("int")
+            .unwrap()
+            .tokens("int")
+            .finish();
+        assert_eq!(format!("{}", m), "#define noreturn(int) int");
+    }
+}
@@ -0,0 +1,240 @@
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Standard {
+    C89,
+    C99,
+    C11,
+    C17,
+    C23,
+    Gnu89,
+    Gnu99,
+    Gnu11,
+    Gnu17,
+    Gnu23,
+}
+
+impl std::fmt::Display for Standard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Standard::C89 => "C89",
+            Standard::C99 => "C99",
+            Standard::C11 => "C11",
+            Standard::C17 => "C17",
+            Standard::C23 => "C23",
+            Standard::Gnu89 => "GNU89",
+            Standard::Gnu99 => "GNU99",
+            Standard::Gnu11 => "GNU11",
+            Standard::Gnu17 => "GNU17",
+            Standard::Gnu23 => "GNU23",
+        })
+    }
+}
+
+// Each keyword with the revision that introduced it
+const KEYWORDS: &[(&str, Standard)] = &[
+    ("auto", Standard::C89),
+    ("break", Standard::C89),
+    ("case", Standard::C89),
+    ("char", Standard::C89),
+    ("const", Standard::C89),
+    ("continue", Standard::C89),
+    ("default", Standard::C89),
+    ("do", Standard::C89),
+    ("double", Standard::C89),
+    ("else", Standard::C89),
+    ("enum", Standard::C89),
+    ("extern", Standard::C89),
+    ("float", Standard::C89),
+    ("for", Standard::C89),
+    ("goto", Standard::C89),
+    ("if", Standard::C89),
+    ("int", Standard::C89),
+    ("long", Standard::C89),
+    ("register", Standard::C89),
+    ("return", Standard::C89),
+    ("short", Standard::C89),
+    ("signed", Standard::C89),
+    ("sizeof", Standard::C89),
+    ("static", Standard::C89),
+    ("struct", Standard::C89),
+    ("switch", Standard::C89),
+    ("typedef", Standard::C89),
+    ("union", Standard::C89),
+    ("unsigned", Standard::C89),
+    ("void", Standard::C89),
+    ("volatile", Standard::C89),
+    ("while", Standard::C89),
+    ("inline", Standard::C99),
+    ("restrict", Standard::C99),
+    ("_Bool", Standard::C99),
+    ("_Complex", Standard::C99),
+    ("_Imaginary", Standard::C99),
+    ("_Alignas", Standard::C11),
+    ("_Alignof", Standard::C11),
+    ("_Atomic", Standard::C11),
+    ("_Generic", Standard::C11),
+    ("_Noreturn", Standard::C11),
+    ("_Static_assert", Standard::C11),
+    ("_Thread_local", Standard::C11),
+    ("alignas", Standard::C23),
+    ("alignof", Standard::C23),
+    ("bool", Standard::C23),
+    ("constexpr", Standard::C23),
+    ("false", Standard::C23),
+    ("nullptr", Standard::C23),
+    ("static_assert", Standard::C23),
+    ("thread_local", Standard::C23),
+    ("true", Standard::C23),
+    ("typeof", Standard::C23),
+    ("typeof_unqual", Standard::C23),
+    ("_BitInt", Standard::C23),
+    ("_Decimal128", Standard::C23),
+    ("_Decimal32", Standard::C23),
+    ("_Decimal64", Standard::C23),
+];
+
+// Headers are often compiled as C++ too, so generated names should avoid these as well
+const CPLUSPLUS_KEYWORDS: &[&str] = &[
+    "and",
+    "and_eq",
+    "asm",
+    "bitand",
+    "bitor",
+    "catch",
+    "char8_t",
+    "char16_t",
+    "char32_t",
+    "class",
+    "compl",
+    "concept",
+    "consteval",
+    "constinit",
+    "const_cast",
+    "co_await",
+    "co_return",
+    "co_yield",
+    "decltype",
+    "delete",
+    "dynamic_cast",
+    "explicit",
+    "export",
+    "friend",
+    "mutable",
+    "namespace",
+    "new",
+    "noexcept",
+    "not",
+    "not_eq",
+    "operator",
+    "or",
+    "or_eq",
+    "private",
+    "protected",
+    "public",
+    "reinterpret_cast",
+    "requires",
+    "static_cast",
+    "template",
+    "this",
+    "throw",
+    "try",
+    "typeid",
+    "typename",
+    "using",
+    "virtual",
+    "wchar_t",
+    "xor",
+    "xor_eq",
+];
+
+// Extensions that GNU dialects treat as keywords
+const GNU_KEYWORDS: &[&str] = &["asm", "inline", "typeof"];
+
+pub(crate) fn is_cplusplus_keyword(name: &str) -> bool {
+    CPLUSPLUS_KEYWORDS.contains(&name)
+}
+
+impl Standard {
+    // The ISO revision that a GNU dialect extends
+    pub fn revision(self) -> Standard {
+        match self {
+            Standard::Gnu89 => Standard::C89,
+            Standard::Gnu99 => Standard::C99,
+            Standard::Gnu11 => Standard::C11,
+            Standard::Gnu17 => Standard::C17,
+            Standard::Gnu23 => Standard::C23,
+            _ => self,
+        }
+    }
+
+    pub fn is_gnu(self) -> bool {
+        self.revision() != self
+    }
+
+    fn year(self) -> usize {
+        match self.revision() {
+            Standard::C89 => 1989,
+            Standard::C99 => 1999,
+            Standard::C11 => 2011,
+            Standard::C17 => 2017,
+            _ => 2023,
+        }
+    }
+
+    // Whether this targets the given revision or a later one
+    pub fn at_least(self, revision: Standard) -> bool {
+        self.year() >= revision.year()
+    }
+
+    // Fail unless the construct is part of this revision, or a GNU extension to it
+    pub(crate) fn require(self, revision: Standard, gnu: bool, construct: &str) -> Result<()> {
+        if self.at_least(revision) || (gnu && self.is_gnu()) {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedConstruct(construct.to_string(), self))
+        }
+    }
+
+    pub fn is_keyword(self, name: &str) -> bool {
+        KEYWORDS
+            .iter()
+            .any(|&(keyword, since)| keyword == name && self.at_least(since))
+            || (self.is_gnu() && GNU_KEYWORDS.contains(&name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords() {
+        assert!(Standard::C89.is_keyword("int"));
+        assert!(!Standard::C89.is_keyword("restrict"));
+        assert!(Standard::C99.is_keyword("restrict"));
+        assert!(!Standard::C17.is_keyword("bool"));
+        assert!(Standard::C23.is_keyword("bool"));
+        assert!(Standard::C23.is_keyword("_Bool"));
+        assert!(!Standard::C23.is_keyword("main"));
+        assert!(!Standard::C89.is_keyword("asm"));
+        assert!(Standard::Gnu89.is_keyword("asm"));
+        assert!(Standard::Gnu89.is_keyword("inline"));
+    }
+
+    #[test]
+    fn revisions() {
+        assert_eq!(Standard::Gnu11.revision(), Standard::C11);
+        assert!(Standard::Gnu11.is_gnu());
+        assert!(!Standard::C11.is_gnu());
+        assert!(Standard::Gnu17.at_least(Standard::C11));
+        assert!(!Standard::C99.at_least(Standard::Gnu11));
+        Standard::Gnu89
+            .require(Standard::C99, true, "long long")
+            .unwrap();
+        assert!(matches!(
+            Standard::C89.require(Standard::C99, true, "long long"),
+            Err(Error::UnsupportedConstruct(_, Standard::C89))
+        ));
+    }
+}
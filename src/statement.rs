@@ -0,0 +1,543 @@
+use crate::attribute::Dialect;
+use crate::declaration::Declaration;
+use crate::error::Result;
+use crate::expression::{Expression, Precedence};
+use crate::identifier::Identifier;
+use crate::standard::Standard;
+
+#[derive(Debug)]
+pub enum BlockItem {
+    Declaration(Declaration),
+    Statement(Box<Statement>),
+}
+
+impl BlockItem {
+    fn render_line(&self, indent: usize) -> String {
+        match self {
+            BlockItem::Declaration(declaration) => format!("{}{}", pad(indent), declaration),
+            BlockItem::Statement(statement) => statement.render_line(indent),
+        }
+    }
+}
+
+impl From<Declaration> for BlockItem {
+    fn from(declaration: Declaration) -> Self {
+        BlockItem::Declaration(declaration)
+    }
+}
+
+impl From<Statement> for BlockItem {
+    fn from(statement: Statement) -> Self {
+        BlockItem::Statement(Box::new(statement))
+    }
+}
+
+#[derive(Debug)]
+pub enum ForInit {
+    Declaration(Declaration),
+    Expression(Expression),
+}
+
+// `For` is large, but boxing its init clause would change the public shape of the variant
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum Statement {
+    Compound(Vec<BlockItem>),
+    Expression(Option<Expression>),
+    If {
+        condition: Expression,
+        then: Box<Statement>,
+        otherwise: Option<Box<Statement>>,
+    },
+    While {
+        condition: Expression,
+        body: Box<Statement>,
+    },
+    DoWhile {
+        body: Box<Statement>,
+        condition: Expression,
+    },
+    For {
+        init: Option<ForInit>,
+        condition: Option<Expression>,
+        step: Option<Expression>,
+        body: Box<Statement>,
+    },
+    Switch {
+        expression: Expression,
+        body: Box<Statement>,
+    },
+    Case {
+        value: Expression,
+        statement: Box<Statement>,
+    },
+    Default(Box<Statement>),
+    Break,
+    Continue,
+    Return(Option<Expression>),
+    Goto(Identifier),
+    Labeled {
+        label: Identifier,
+        statement: Box<Statement>,
+    },
+}
+
+fn pad(indent: usize) -> String {
+    "    ".repeat(indent)
+}
+
+pub(crate) fn render_block(items: &[BlockItem], indent: usize) -> String {
+    let mut s = "{\n".to_string();
+    for item in items {
+        s.push_str(&item.render_line(indent + 1));
+        s.push('\n');
+    }
+    s.push_str(&pad(indent));
+    s.push('}');
+    s
+}
+
+// Rewrite or reject constructs that the target standard lacks
+pub(crate) fn lower_block(
+    items: &mut [BlockItem],
+    standard: Standard,
+    dialect: Dialect,
+) -> Result<()> {
+    let mut seen_statement = false;
+    for item in items {
+        match item {
+            BlockItem::Declaration(declaration) => {
+                if seen_statement {
+                    standard.require(Standard::C99, true, "mixed declarations and code")?;
+                }
+                declaration.lower(standard, dialect)?;
+            }
+            BlockItem::Statement(statement) => {
+                seen_statement = true;
+                statement.lower(standard, dialect)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Statement {
+    pub(crate) fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        let expressions = |expressions: &mut [&mut Option<Expression>]| {
+            expressions
+                .iter_mut()
+                .filter_map(|expression| expression.as_mut())
+                .try_for_each(|expression| expression.lower(standard, dialect))
+        };
+        match self {
+            Statement::Compound(items) => lower_block(items, standard, dialect),
+            Statement::Expression(expression) | Statement::Return(expression) => {
+                expressions(&mut [expression])
+            }
+            Statement::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                condition.lower(standard, dialect)?;
+                then.lower(standard, dialect)?;
+                match otherwise {
+                    Some(otherwise) => otherwise.lower(standard, dialect),
+                    None => Ok(()),
+                }
+            }
+            Statement::While { condition, body }
+            | Statement::DoWhile { body, condition }
+            | Statement::Switch {
+                expression: condition,
+                body,
+            }
+            | Statement::Case {
+                value: condition,
+                statement: body,
+            } => {
+                condition.lower(standard, dialect)?;
+                body.lower(standard, dialect)
+            }
+            Statement::For {
+                init,
+                condition,
+                step,
+                body,
+            } => {
+                match init {
+                    Some(ForInit::Declaration(declaration)) => {
+                        standard.require(Standard::C99, true, "declarations in for loops")?;
+                        declaration.lower(standard, dialect)?;
+                    }
+                    Some(ForInit::Expression(expression)) => expression.lower(standard, dialect)?,
+                    None => {}
+                }
+                expressions(&mut [condition, step])?;
+                body.lower(standard, dialect)
+            }
+            Statement::Default(statement) | Statement::Labeled { statement, .. } => {
+                statement.lower(standard, dialect)
+            }
+            Statement::Break | Statement::Continue | Statement::Goto(_) => Ok(()),
+        }
+    }
+
+    // Labels are outdented one level from the statement they mark
+    fn render_line(&self, indent: usize) -> String {
+        let level = match self {
+            Statement::Case { .. } | Statement::Default(_) | Statement::Labeled { .. } => {
+                indent.saturating_sub(1)
+            }
+            _ => indent,
+        };
+        format!("{}{}", pad(level), self.render(indent))
+    }
+
+    // Render a loop or selection body, keeping compound statements on the same line
+    fn render_body(&self, indent: usize) -> String {
+        match self {
+            Statement::Compound(items) => format!(" {}", render_block(items, indent)),
+            _ => format!("\n{}", self.render_line(indent + 1)),
+        }
+    }
+
+    // Whether an `else` following this statement would bind to a nested `if`
+    fn has_dangling_if(&self) -> bool {
+        match self {
+            Statement::If {
+                otherwise: None, ..
+            } => true,
+            Statement::If {
+                otherwise: Some(otherwise),
+                ..
+            } => otherwise.has_dangling_if(),
+            Statement::While { body, .. }
+            | Statement::For { body, .. }
+            | Statement::Switch { body, .. } => body.has_dangling_if(),
+            Statement::Case { statement, .. } | Statement::Labeled { statement, .. } => {
+                statement.has_dangling_if()
+            }
+            Statement::Default(statement) => statement.has_dangling_if(),
+            _ => false,
+        }
+    }
+
+    fn render(&self, indent: usize) -> String {
+        match self {
+            Statement::Compound(items) => render_block(items, indent),
+            Statement::Expression(Some(expression)) => format!("{};", expression),
+            Statement::Expression(None) => ";".to_string(),
+            Statement::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let mut s = format!("if ({})", condition);
+                let braced = otherwise.is_some() && then.has_dangling_if();
+                if braced {
+                    s.push_str(&format!(
+                        " {{\n{}\n{}}}",
+                        then.render_line(indent + 1),
+                        pad(indent)
+                    ));
+                } else {
+                    s.push_str(&then.render_body(indent));
+                }
+                if let Some(otherwise) = otherwise {
+                    if braced || matches!(**then, Statement::Compound(_)) {
+                        s.push_str(" else");
+                    } else {
+                        s.push_str(&format!("\n{}else", pad(indent)));
+                    }
+                    if let Statement::If { .. } = **otherwise {
+                        s.push_str(&format!(" {}", otherwise.render(indent)));
+                    } else {
+                        s.push_str(&otherwise.render_body(indent));
+                    }
+                }
+                s
+            }
+            Statement::While { condition, body } => {
+                format!("while ({}){}", condition, body.render_body(indent))
+            }
+            Statement::DoWhile { body, condition } => {
+                let separator = if let Statement::Compound(_) = **body {
+                    " ".to_string()
+                } else {
+                    format!("\n{}", pad(indent))
+                };
+                format!(
+                    "do{}{}while ({});",
+                    body.render_body(indent),
+                    separator,
+                    condition
+                )
+            }
+            Statement::For {
+                init,
+                condition,
+                step,
+                body,
+            } => {
+                let mut s = "for (".to_string();
+                match init {
+                    Some(ForInit::Declaration(declaration)) => s.push_str(&declaration.to_string()),
+                    Some(ForInit::Expression(expression)) => {
+                        s.push_str(&format!("{};", expression))
+                    }
+                    None => s.push(';'),
+                }
+                if let Some(condition) = condition {
+                    s.push_str(&format!(" {}", condition));
+                }
+                s.push(';');
+                if let Some(step) = step {
+                    s.push_str(&format!(" {}", step));
+                }
+                s.push(')');
+                s.push_str(&body.render_body(indent));
+                s
+            }
+            Statement::Switch { expression, body } => {
+                format!("switch ({}){}", expression, body.render_body(indent))
+            }
+            Statement::Case { value, statement } => {
+                format!(
+                    "case {}:\n{}",
+                    value.render_operand(Precedence::Conditional),
+                    statement.render_line(indent)
+                )
+            }
+            Statement::Default(statement) => {
+                format!("default:\n{}", statement.render_line(indent))
+            }
+            Statement::Break => "break;".to_string(),
+            Statement::Continue => "continue;".to_string(),
+            Statement::Return(Some(expression)) => format!("return {};", expression),
+            Statement::Return(None) => "return;".to_string(),
+            Statement::Goto(label) => format!("goto {};", label),
+            Statement::Labeled { label, statement } => {
+                format!("{}:\n{}", label, statement.render_line(indent))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Statement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render_line(0))
+    }
+}
+
+pub fn block(items: Vec<BlockItem>) -> Statement {
+    Statement::Compound(items)
+}
+
+pub fn expression_statement(expression: Expression) -> Statement {
+    Statement::Expression(Some(expression))
+}
+
+pub fn null_statement() -> Statement {
+    Statement::Expression(None)
+}
+
+pub fn if_then(condition: Expression, then: Statement) -> Statement {
+    Statement::If {
+        condition,
+        then: Box::new(then),
+        otherwise: None,
+    }
+}
+
+pub fn if_then_else(condition: Expression, then: Statement, otherwise: Statement) -> Statement {
+    Statement::If {
+        condition,
+        then: Box::new(then),
+        otherwise: Some(Box::new(otherwise)),
+    }
+}
+
+pub fn while_loop(condition: Expression, body: Statement) -> Statement {
+    Statement::While {
+        condition,
+        body: Box::new(body),
+    }
+}
+
+pub fn do_while(body: Statement, condition: Expression) -> Statement {
+    Statement::DoWhile {
+        body: Box::new(body),
+        condition,
+    }
+}
+
+pub fn for_loop(
+    init: Option<ForInit>,
+    condition: Option<Expression>,
+    step: Option<Expression>,
+    body: Statement,
+) -> Statement {
+    Statement::For {
+        init,
+        condition,
+        step,
+        body: Box::new(body),
+    }
+}
+
+pub fn switch(expression: Expression, body: Statement) -> Statement {
+    Statement::Switch {
+        expression,
+        body: Box::new(body),
+    }
+}
+
+pub fn case(value: Expression, statement: Statement) -> Statement {
+    Statement::Case {
+        value,
+        statement: Box::new(statement),
+    }
+}
+
+pub fn default_case(statement: Statement) -> Statement {
+    Statement::Default(Box::new(statement))
+}
+
+pub fn r#break() -> Statement {
+    Statement::Break
+}
+
+pub fn r#continue() -> Statement {
+    Statement::Continue
+}
+
+pub fn r#return(expression: Option<Expression>) -> Statement {
+    Statement::Return(expression)
+}
+
+pub fn goto<S: Into<String>>(label: S) -> Result<Statement> {
+    Ok(Statement::Goto(Identifier::new(label.into())?))
+}
+
+pub fn labeled<S: Into<String>>(label: S, statement: Statement) -> Result<Statement> {
+    Ok(Statement::Labeled {
+        label: Identifier::new(label.into())?,
+        statement: Box::new(statement),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::declaration::DeclarationBuilder;
+    use crate::expression::{identifier, less_than, literal, postfix_inc};
+    use crate::initializer::Initializer;
+    use crate::literal::Literal;
+    use crate::r#type::TypeBuilder;
+
+    fn int(value: i128) -> Expression {
+        literal(Literal::Signed(value))
+    }
+
+    #[test]
+    fn compound() {
+        let s = block(vec![
+            DeclarationBuilder::new()
+                .declarator_with_initializer(
+                    TypeBuilder::new().int(),
+                    "x",
+                    Initializer::Expression(int(0)),
+                )
+                .unwrap()
+                .finish()
+                .unwrap()
+                .into(),
+            expression_statement(postfix_inc(identifier("x").unwrap())).into(),
+            r#return(Some(identifier("x").unwrap())).into(),
+        ]);
+        assert_eq!(
+            format!("{}", s),
+            "{\n    int x = 0;\n    x++;\n    return x;\n}"
+        );
+    }
+
+    #[test]
+    fn if_else_chain() {
+        let s = if_then_else(
+            identifier("a").unwrap(),
+            block(vec![r#return(Some(int(1))).into()]),
+            if_then_else(
+                identifier("b").unwrap(),
+                r#return(Some(int(2))),
+                r#return(None),
+            ),
+        );
+        assert_eq!(
+            format!("{}", s),
+            "if (a) {\n    return 1;\n} else if (b)\n    return 2;\nelse\n    return;"
+        );
+    }
+
+    #[test]
+    fn dangling_else() {
+        let s = if_then_else(
+            identifier("a").unwrap(),
+            while_loop(
+                identifier("b").unwrap(),
+                if_then(identifier("c").unwrap(), r#break()),
+            ),
+            r#continue(),
+        );
+        assert_eq!(
+            format!("{}", s),
+            "if (a) {\n    while (b)\n        if (c)\n            break;\n} else\n    continue;"
+        );
+    }
+
+    #[test]
+    fn loops() {
+        let i = DeclarationBuilder::new()
+            .declarator_with_initializer(
+                TypeBuilder::new().int(),
+                "i",
+                Initializer::Expression(int(0)),
+            )
+            .unwrap()
+            .finish()
+            .unwrap();
+        let s = for_loop(
+            Some(ForInit::Declaration(i)),
+            Some(less_than(identifier("i").unwrap(), int(4))),
+            Some(postfix_inc(identifier("i").unwrap())),
+            do_while(block(Vec::new()), int(0)),
+        );
+        assert_eq!(
+            format!("{}", s),
+            "for (int i = 0; i < 4; i++)\n    do {\n    } while (0);"
+        );
+        assert_eq!(
+            format!("{}", for_loop(None, None, None, null_statement())),
+            "for (;;)\n    ;"
+        );
+    }
+
+    #[test]
+    fn switch_labels() {
+        let s = block(vec![
+            switch(
+                identifier("x").unwrap(),
+                block(vec![
+                    case(int(1), goto("out").unwrap()).into(),
+                    default_case(r#break()).into(),
+                ]),
+            )
+            .into(),
+            labeled("out", r#return(None)).unwrap().into(),
+        ]);
+        assert_eq!(
+            format!("{}", s),
+            "{\n    switch (x) {\n    case 1:\n        goto out;\n    default:\n        break;\n    }\nout:\n    return;\n}"
+        );
+    }
+}
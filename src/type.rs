@@ -1,25 +1,71 @@
-use crate::error::Result;
-use crate::expression::Expression;
+use crate::attribute::{Attribute, Attributes, CallingConvention, Dialect, Subject};
+use crate::declaration::Declaration;
+use crate::error::{Error, Result};
+use crate::expression::{Expression, Precedence};
+use crate::file::Include;
 use crate::identifier::Identifier;
+use crate::standard::Standard;
 
 #[derive(Debug)]
-enum StructType {
+pub(crate) enum StructType {
     Struct,
     Union,
 }
 
 #[derive(Debug)]
-struct Field {
-    r#type: Box<Type>,
-    name: Option<Identifier>,
-    width: Option<Box<Expression>>,
+pub(crate) struct Field {
+    pub(crate) r#type: Box<Type>,
+    pub(crate) name: Option<Identifier>,
+    pub(crate) width: Option<Box<Expression>>,
+    pub(crate) attributes: Attributes,
+}
+
+impl Field {
+    fn subject(&self) -> Subject {
+        if self.width.is_some() {
+            Subject::BitField
+        } else {
+            Subject::Member
+        }
+    }
+
+    fn is_flexible(&self) -> bool {
+        matches!(
+            self.r#type.modifiers.first(),
+            Some(TypeModifier::Array(None))
+        )
+    }
+
+    fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        if self.name.is_none() && self.width.is_none() {
+            standard.require(Standard::C11, true, "anonymous members")?;
+        }
+        if self.is_flexible() {
+            standard.require(Standard::C99, true, "flexible array members")?;
+        }
+        if let Some(width) = &mut self.width {
+            width.lower(standard, dialect)?;
+        }
+        self.attributes.lower(standard, dialect, self.subject())?;
+        self.r#type.lower(standard, dialect)
+    }
 }
 
 impl std::fmt::Display for Field {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = self.r#type.render(self.name.as_ref());
+        let s = format!(
+            "{}{}{}",
+            self.attributes.render_leading(),
+            self.attributes.render_prefix(),
+            self.r#type.render(self.name.as_ref())
+        );
         if let Some(width) = &self.width {
-            write!(f, "{} : {};", s, width)
+            write!(
+                f,
+                "{} : {};",
+                s,
+                width.render_operand(Precedence::Conditional)
+            )
         } else {
             write!(f, "{};", s)
         }
@@ -27,15 +73,20 @@ impl std::fmt::Display for Field {
 }
 
 #[derive(Debug)]
-struct Enumerator {
-    name: Identifier,
-    value: Option<Box<Expression>>,
+pub(crate) struct Enumerator {
+    pub(crate) name: Identifier,
+    pub(crate) value: Option<Box<Expression>>,
 }
 
 impl std::fmt::Display for Enumerator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(value) = &self.value {
-            write!(f, "{} = {}", self.name, value)
+            write!(
+                f,
+                "{} = {}",
+                self.name,
+                value.render_operand(Precedence::Conditional)
+            )
         } else {
             write!(f, "{}", self.name)
         }
@@ -43,9 +94,12 @@ impl std::fmt::Display for Enumerator {
 }
 
 #[derive(Debug)]
-struct Qualifiers {
+pub(crate) struct Qualifiers {
     constant: bool,
     volatile: bool,
+    // Only valid on pointers, which `RestrictedPointerBuilder` enforces
+    restrict: bool,
+    pub(crate) atomic: bool,
 }
 
 impl Qualifiers {
@@ -53,35 +107,104 @@ impl Qualifiers {
         Self {
             constant: false,
             volatile: false,
+            restrict: false,
+            atomic: false,
         }
     }
 
     fn is_none(&self) -> bool {
-        !self.constant && !self.volatile
+        !self.constant && !self.volatile && !self.restrict && !self.atomic
+    }
+
+    fn lower(&self, standard: Standard) -> Result<()> {
+        if self.restrict {
+            standard.require(Standard::C99, false, "restrict")?;
+        }
+        if self.atomic {
+            standard.require(Standard::C11, true, "_Atomic")?;
+        }
+        Ok(())
     }
 }
 
 impl std::fmt::Display for Qualifiers {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.constant {
-            f.write_str("const")?;
-        };
-        if self.constant && self.volatile {
-            f.write_str(" ")?;
+        let qualifiers = [
+            (self.constant, "const"),
+            (self.volatile, "volatile"),
+            (self.restrict, "restrict"),
+            (self.atomic, "_Atomic"),
+        ];
+        let names = qualifiers
+            .iter()
+            .filter(|(present, _)| *present)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+        f.write_str(&names.join(" "))
+    }
+}
+
+// Typedefs from the standard library headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibraryType {
+    Size,
+    PtrDiff,
+    WChar,
+    MaxAlign,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    IntPtr,
+    UIntPtr,
+    IntMax,
+    UIntMax,
+}
+
+impl LibraryType {
+    pub fn name(self) -> &'static str {
+        match self {
+            LibraryType::Size => "size_t",
+            LibraryType::PtrDiff => "ptrdiff_t",
+            LibraryType::WChar => "wchar_t",
+            LibraryType::MaxAlign => "max_align_t",
+            LibraryType::Int8 => "int8_t",
+            LibraryType::Int16 => "int16_t",
+            LibraryType::Int32 => "int32_t",
+            LibraryType::Int64 => "int64_t",
+            LibraryType::UInt8 => "uint8_t",
+            LibraryType::UInt16 => "uint16_t",
+            LibraryType::UInt32 => "uint32_t",
+            LibraryType::UInt64 => "uint64_t",
+            LibraryType::IntPtr => "intptr_t",
+            LibraryType::UIntPtr => "uintptr_t",
+            LibraryType::IntMax => "intmax_t",
+            LibraryType::UIntMax => "uintmax_t",
+        }
+    }
+
+    pub fn header(self) -> &'static str {
+        match self {
+            LibraryType::Size
+            | LibraryType::PtrDiff
+            | LibraryType::WChar
+            | LibraryType::MaxAlign => "stddef.h",
+            _ => "stdint.h",
         }
-        if self.volatile {
-            f.write_str("volatile")?;
-        };
-        Ok(())
     }
 }
 
 #[derive(Debug)]
-enum BaseType {
+pub(crate) enum BaseType {
     Struct {
         name: Option<Identifier>,
         struct_type: StructType,
         fields: Option<Vec<Field>>,
+        attributes: Attributes,
     },
     Enum {
         name: Option<Identifier>,
@@ -89,6 +212,17 @@ enum BaseType {
     },
     Alias(Identifier),
     Void,
+    Bool,
+    UnderscoreBool,
+    // The `_Atomic(T)` specifier, as opposed to the qualifier
+    Atomic(Box<Type>),
+    Library(LibraryType),
+    BitInt {
+        width: u32,
+        unsigned: bool,
+    },
+    Signed,
+    Unsigned,
     Char,
     SignedChar,
     UnsignedChar,
@@ -103,6 +237,9 @@ enum BaseType {
     Float,
     Double,
     LongDouble,
+    FloatComplex,
+    DoubleComplex,
+    LongDoubleComplex,
 }
 
 impl std::fmt::Display for BaseType {
@@ -112,20 +249,32 @@ impl std::fmt::Display for BaseType {
                 name,
                 struct_type,
                 fields,
+                attributes,
             } => {
+                let pack = attributes.render_pack();
+                if let Some((push, _)) = pack {
+                    f.write_str(push)?;
+                }
                 f.write_str(match struct_type {
                     StructType::Struct => "struct",
                     StructType::Union => "union",
                 })?;
+                let tag = attributes.render_tag();
+                if !tag.is_empty() {
+                    write!(f, " {}", tag.trim_end())?;
+                }
                 if let Some(name) = name {
                     write!(f, " {}", name)?;
                 }
                 if let Some(fields) = fields {
-                    f.write_str(" {");
+                    f.write_str(" {")?;
                     for field in fields {
                         write!(f, " {}", field)?;
                     }
-                    f.write_str(" }");
+                    f.write_str(" }")?;
+                }
+                if let Some((_, pop)) = pack {
+                    f.write_str(pop)?;
                 }
                 Ok(())
             }
@@ -135,19 +284,33 @@ impl std::fmt::Display for BaseType {
                     write!(f, " {}", name)?;
                 }
                 if let Some(enumerators) = enumerators {
-                    f.write_str(" {");
+                    f.write_str(" {")?;
                     for (i, enumerator) in enumerators.iter().enumerate() {
                         write!(f, " {}", enumerator)?;
                         if i < enumerators.len() - 1 {
-                            f.write_str(",");
+                            f.write_str(",")?;
                         }
                     }
-                    f.write_str(" }");
+                    f.write_str(" }")?;
                 }
                 Ok(())
             }
             BaseType::Alias(identifier) => write!(f, "{}", identifier),
             BaseType::Void => f.write_str("void"),
+            BaseType::Bool => f.write_str("bool"),
+            BaseType::UnderscoreBool => f.write_str("_Bool"),
+            BaseType::Atomic(inner) => write!(f, "_Atomic({})", inner),
+            BaseType::Library(library) => f.write_str(library.name()),
+            BaseType::BitInt {
+                width,
+                unsigned: false,
+            } => write!(f, "_BitInt({})", width),
+            BaseType::BitInt {
+                width,
+                unsigned: true,
+            } => write!(f, "unsigned _BitInt({})", width),
+            BaseType::Signed => f.write_str("signed"),
+            BaseType::Unsigned => f.write_str("unsigned"),
             BaseType::Char => f.write_str("char"),
             BaseType::SignedChar => f.write_str("signed char"),
             BaseType::UnsignedChar => f.write_str("unsigned char"),
@@ -162,26 +325,186 @@ impl std::fmt::Display for BaseType {
             BaseType::Float => f.write_str("float"),
             BaseType::Double => f.write_str("double"),
             BaseType::LongDouble => f.write_str("long double"),
+            BaseType::FloatComplex => f.write_str("float _Complex"),
+            BaseType::DoubleComplex => f.write_str("double _Complex"),
+            BaseType::LongDoubleComplex => f.write_str("long double _Complex"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Parameter {
+    pub(crate) r#type: Type,
+    pub(crate) name: Option<Identifier>,
+}
+
+impl std::fmt::Display for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.r#type.render(self.name.as_ref()))
+    }
+}
+
+#[derive(Debug)]
+pub struct ParameterList {
+    pub(crate) parameters: Vec<Parameter>,
+    variadic: bool,
+    // Whether the parameters are declared at all, so an empty list renders `(void)` not `()`
+    prototype: bool,
+    convention: Option<CallingConvention>,
+    dialect: Dialect,
+}
+
+impl Default for ParameterList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParameterList {
+    pub fn new() -> Self {
+        Self {
+            parameters: Vec::new(),
+            variadic: false,
+            prototype: true,
+            convention: None,
+            dialect: Dialect::default(),
+        }
+    }
+
+    // An old-style declaration that says nothing about the parameters, rendered as `()`
+    pub fn unspecified() -> Self {
+        Self {
+            parameters: Vec::new(),
+            variadic: false,
+            prototype: false,
+            convention: None,
+            dialect: Dialect::default(),
         }
     }
+
+    pub fn parameter<S: Into<String>>(mut self, r#type: Type, name: S) -> Result<Self> {
+        let name = Identifier::new(name.into())?;
+        if self
+            .parameters
+            .iter()
+            .any(|p| p.name.as_ref() == Some(&name))
+        {
+            return Err(Error::DuplicateParameter(name.to_string()));
+        }
+        self.parameters.push(Parameter {
+            r#type,
+            name: Some(name),
+        });
+        self.prototype = true;
+        Ok(self)
+    }
+
+    pub fn unnamed_parameter(mut self, r#type: Type) -> Self {
+        self.parameters.push(Parameter { r#type, name: None });
+        self.prototype = true;
+        self
+    }
+
+    pub fn variadic(mut self) -> Self {
+        self.variadic = true;
+        self.prototype = true;
+        self
+    }
+
+    pub fn calling_convention(mut self, convention: CallingConvention) -> Self {
+        self.convention = Some(convention);
+        self
+    }
+
+    fn render_convention(&self) -> Option<String> {
+        self.convention
+            .map(|convention| convention.render(self.dialect))
+    }
+
+    fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        if let Some(convention) = self.convention {
+            convention.lower(dialect)?;
+        }
+        self.dialect = dialect;
+        if self.variadic && self.parameters.is_empty() {
+            standard.require(
+                Standard::C23,
+                false,
+                "variadic functions without named parameters",
+            )?;
+        }
+        for parameter in &mut self.parameters {
+            parameter.r#type.lower(standard, dialect)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ParameterList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut list = self
+            .parameters
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>();
+        if self.variadic {
+            list.push("...".to_string());
+        }
+        if list.is_empty() && self.prototype {
+            list.push("void".to_string());
+        }
+        write!(f, "({})", list.join(", "))
+    }
 }
 
 #[derive(Debug)]
-enum TypeModifier {
+pub(crate) enum TypeModifier {
     Pointer(Qualifiers),
     Array(Option<Box<Expression>>),
-    Function(Vec<Type>),
+    Function(ParameterList),
+}
+
+impl TypeModifier {
+    // Functions can't return arrays or functions, and arrays can't hold functions
+    fn nesting_error(&self, inner: &TypeModifier) -> Option<fn(String) -> Error> {
+        match (self, inner) {
+            (TypeModifier::Function(_), TypeModifier::Array(_))
+            | (TypeModifier::Function(_), TypeModifier::Function(_)) => {
+                Some(Error::InvalidReturnType)
+            }
+            (TypeModifier::Array(_), TypeModifier::Function(_)) => Some(Error::InvalidElementType),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Type {
-    base: BaseType,
-    qualifiers: Qualifiers,
-    modifiers: Vec<TypeModifier>,
+    pub(crate) base: BaseType,
+    pub(crate) qualifiers: Qualifiers,
+    pub(crate) modifiers: Vec<TypeModifier>,
 }
 
 impl Type {
     pub(crate) fn render(&self, name: Option<&Identifier>) -> String {
+        let specifiers = self.render_specifiers();
+        let declarator = self.render_declarator(name);
+        if declarator.is_empty() {
+            specifiers
+        } else {
+            format!("{} {}", specifiers, declarator)
+        }
+    }
+
+    pub(crate) fn render_specifiers(&self) -> String {
+        if self.qualifiers.is_none() {
+            format!("{}", self.base)
+        } else {
+            format!("{} {}", self.qualifiers, self.base)
+        }
+    }
+
+    pub(crate) fn render_declarator(&self, name: Option<&Identifier>) -> String {
         let mut v = if let Some(name) = name {
             vec![format!("{}", name)]
         } else {
@@ -206,7 +529,12 @@ impl Type {
                         v.insert(1, " ".to_string());
                     }
                 }
-                TypeModifier::Function(args) => {
+                TypeModifier::Function(parameters) => {
+                    // The calling convention goes just before the inner declarator
+                    if let Some(convention) = parameters.render_convention() {
+                        v.insert(0, format!("{} ", convention));
+                    }
+
                     // Push us back to the right if we're on the left
                     if !right && !v.is_empty() {
                         v.insert(0, "(".to_string());
@@ -215,15 +543,7 @@ impl Type {
                     right = true;
 
                     // Print the function arguments
-                    v.push("(".to_string());
-                    v.extend(args.iter().enumerate().map(|(i, x)| {
-                        if i > 0 {
-                            format!(", {}", x)
-                        } else {
-                            format!("{}", x)
-                        }
-                    }));
-                    v.push(")".to_string());
+                    v.push(parameters.to_string());
                 }
                 TypeModifier::Array(size) => {
                     // Push us back to the right if we're on the left
@@ -236,25 +556,146 @@ impl Type {
                     // Print the array and size
                     v.push("[".to_string());
                     if let Some(size) = size {
-                        v.push(format!("{}", size))
+                        v.push(size.render_operand(Precedence::Assignment))
                     }
                     v.push("]".to_string());
                 }
             }
         }
-        v.insert(
-            0,
-            if self.qualifiers.is_none() {
-                format!("{}", self.base)
-            } else {
-                format!("{} {}", self.qualifiers, self.base)
-            },
-        );
-        if v.len() > 1 {
-            v.insert(1, " ".to_string());
-        }
         v.join("")
     }
+
+    // Rewrite or reject constructs that the target standard lacks
+    pub(crate) fn lower(&mut self, standard: Standard, dialect: Dialect) -> Result<()> {
+        match &mut self.base {
+            BaseType::LongLong | BaseType::UnsignedLongLong => {
+                standard.require(Standard::C99, true, "long long")?
+            }
+            // `bool` needs <stdbool.h> before C23, where `_Bool` becomes obsolescent instead
+            BaseType::Bool | BaseType::UnderscoreBool => {
+                standard.require(Standard::C99, true, "_Bool")?;
+                self.base = if standard.at_least(Standard::C23) {
+                    BaseType::Bool
+                } else {
+                    BaseType::UnderscoreBool
+                };
+            }
+            BaseType::FloatComplex | BaseType::DoubleComplex | BaseType::LongDoubleComplex => {
+                standard.require(Standard::C99, true, "_Complex")?
+            }
+            BaseType::BitInt { .. } => standard.require(Standard::C23, false, "_BitInt")?,
+            // GCC's <stddef.h> only declares `max_align_t` for C11 and later
+            BaseType::Library(LibraryType::MaxAlign) => {
+                standard.require(Standard::C11, false, "max_align_t")?
+            }
+            BaseType::Library(library) if library.header() == "stdint.h" => {
+                standard.require(Standard::C99, true, "<stdint.h>")?
+            }
+            BaseType::Atomic(inner) => {
+                standard.require(Standard::C11, true, "_Atomic")?;
+                inner.lower(standard, dialect)?;
+            }
+            BaseType::Struct {
+                fields: Some(fields),
+                attributes,
+                ..
+            } => {
+                attributes.lower(standard, dialect, Subject::Type)?;
+                for field in fields {
+                    field.lower(standard, dialect)?;
+                }
+            }
+            BaseType::Enum {
+                enumerators: Some(enumerators),
+                ..
+            } => {
+                for enumerator in enumerators {
+                    if let Some(value) = &mut enumerator.value {
+                        value.lower(standard, dialect)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.qualifiers.lower(standard)?;
+        for pair in self.modifiers.windows(2) {
+            if let Some(error) = pair[0].nesting_error(&pair[1]) {
+                return Err(error(self.to_string()));
+            }
+        }
+        for modifier in &mut self.modifiers {
+            match modifier {
+                TypeModifier::Pointer(qualifiers) => qualifiers.lower(standard)?,
+                TypeModifier::Array(Some(size)) => size.lower(standard, dialect)?,
+                TypeModifier::Function(parameters) => parameters.lower(standard, dialect)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Headers that must be included for this type to be declared
+    pub fn required_includes(&self) -> Vec<Include> {
+        let mut includes = Vec::new();
+        self.collect_includes(&mut includes);
+        includes
+    }
+
+    pub(crate) fn collect_includes(&self, includes: &mut Vec<Include>) {
+        let mut add = |header| {
+            let include = Include::bracket(header);
+            if !includes.contains(&include) {
+                includes.push(include);
+            }
+        };
+        match &self.base {
+            BaseType::Library(library) => add(library.header()),
+            // Before C23, `bool` is a macro for `_Bool`
+            BaseType::Bool => add("stdbool.h"),
+            BaseType::Atomic(inner) => inner.collect_includes(includes),
+            BaseType::Struct {
+                fields: Some(fields),
+                ..
+            } => {
+                for field in fields {
+                    field.r#type.collect_includes(includes);
+                }
+            }
+            _ => {}
+        }
+        for modifier in &self.modifiers {
+            if let TypeModifier::Function(parameters) = modifier {
+                for parameter in &parameters.parameters {
+                    parameter.r#type.collect_includes(includes);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn has_modifiers(&self) -> bool {
+        !self.modifiers.is_empty()
+    }
+
+    // Wrap this type as the return type of a function prototype
+    pub(crate) fn into_function_returning(mut self, parameters: ParameterList) -> Result<Type> {
+        let function = TypeModifier::Function(parameters);
+        if let Some(error) = self
+            .modifiers
+            .first()
+            .and_then(|inner| function.nesting_error(inner))
+        {
+            return Err(error(self.to_string()));
+        }
+        self.modifiers.insert(0, function);
+        Ok(self)
+    }
+
+    pub(crate) fn function_parameters(&self) -> Option<&[Parameter]> {
+        match self.modifiers.first() {
+            Some(TypeModifier::Function(parameters)) => Some(&parameters.parameters),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for Type {
@@ -264,21 +705,26 @@ impl std::fmt::Display for Type {
 }
 
 macro_rules! terminate {
-    ($func_name:ident, $base_type:ident) => {
+    ($func_name:ident, $($base_type:tt)+) => {
         pub fn $func_name(self: Self) -> Type {
             let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
             Type {
-                base: BaseType::$base_type,
-                qualifiers: qualifiers,
-                modifiers: modifiers,
+                base: BaseType::$($base_type)+,
+                qualifiers,
+                modifiers,
             }
         }
     };
 }
 
+#[rustfmt::skip]
 macro_rules! implement_type_builder {
     () => (
     terminate!(void, Void);
+    terminate!(bool, Bool);
+    terminate!(underscore_bool, UnderscoreBool);
+    terminate!(signed, Signed);
+    terminate!(unsigned, Unsigned);
     terminate!(char, Char);
     terminate!(unsigned_char, UnsignedChar);
     terminate!(signed_char, SignedChar);
@@ -293,21 +739,89 @@ macro_rules! implement_type_builder {
     terminate!(float, Float);
     terminate!(double, Double);
     terminate!(long_double, LongDouble);
+    terminate!(float_complex, FloatComplex);
+    terminate!(double_complex, DoubleComplex);
+    terminate!(long_double_complex, LongDoubleComplex);
+    terminate!(size_t, Library(LibraryType::Size));
+    terminate!(ptrdiff_t, Library(LibraryType::PtrDiff));
+    terminate!(wchar_t, Library(LibraryType::WChar));
+    terminate!(max_align_t, Library(LibraryType::MaxAlign));
+    terminate!(int8_t, Library(LibraryType::Int8));
+    terminate!(int16_t, Library(LibraryType::Int16));
+    terminate!(int32_t, Library(LibraryType::Int32));
+    terminate!(int64_t, Library(LibraryType::Int64));
+    terminate!(uint8_t, Library(LibraryType::UInt8));
+    terminate!(uint16_t, Library(LibraryType::UInt16));
+    terminate!(uint32_t, Library(LibraryType::UInt32));
+    terminate!(uint64_t, Library(LibraryType::UInt64));
+    terminate!(intptr_t, Library(LibraryType::IntPtr));
+    terminate!(uintptr_t, Library(LibraryType::UIntPtr));
+    terminate!(intmax_t, Library(LibraryType::IntMax));
+    terminate!(uintmax_t, Library(LibraryType::UIntMax));
+
+    pub fn library(self, library: LibraryType) -> Type {
+        let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
+        Type {
+            base: BaseType::Library(library),
+            qualifiers,
+            modifiers,
+        }
+    }
+
+    // Arrays, functions, qualified and atomic types can't be made atomic
+    pub fn atomic_of(self, r#type: Type) -> Result<Type> {
+        // The top-level qualifiers of a pointer are those on the outermost pointer
+        let invalid = match r#type.modifiers.first() {
+            Some(TypeModifier::Pointer(qualifiers)) => !qualifiers.is_none(),
+            Some(TypeModifier::Array(_)) | Some(TypeModifier::Function(_)) => true,
+            None => !r#type.qualifiers.is_none() || matches!(r#type.base, BaseType::Atomic(_)),
+        };
+        if invalid {
+            return Err(Error::InvalidAtomicType(r#type.to_string()));
+        }
+        let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
+        Ok(Type {
+            base: BaseType::Atomic(Box::new(r#type)),
+            qualifiers,
+            modifiers,
+        })
+    }
+
+    // C23 bit-precise integers; signed ones need a sign bit and at least one value bit
+    pub fn bit_int(self, width: u32) -> Result<Type> {
+        self.bit_precise(width, false)
+    }
+
+    pub fn unsigned_bit_int(self, width: u32) -> Result<Type> {
+        self.bit_precise(width, true)
+    }
+
+    fn bit_precise(self, width: u32, unsigned: bool) -> Result<Type> {
+        if width < if unsigned { 1 } else { 2 } {
+            return Err(Error::InvalidBitIntWidth(width));
+        }
+        let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
+        Ok(Type {
+            base: BaseType::BitInt { width, unsigned },
+            qualifiers,
+            modifiers,
+        })
+    }
 
     pub fn alias_named<S: Into<String>>(self, name: S) -> Result<Type> {
         let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
         Ok(Type {
             base: BaseType::Alias(Identifier::new(name.into())?),
-            qualifiers: qualifiers,
-            modifiers: modifiers,
+            qualifiers,
+            modifiers,
         })
     }
 
     pub fn struct_named<S: Into<String>>(self, name: S) -> Result<StructBuilder> {
         let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
         Ok(StructBuilder {
-            qualifiers: qualifiers,
-            modifiers: modifiers,
+            qualifiers,
+            modifiers,
             name: Identifier::new(name.into())?,
             struct_type: StructType::Struct,
         })
@@ -316,8 +830,8 @@ macro_rules! implement_type_builder {
     pub fn union_named<S: Into<String>>(self, name: S) -> Result<StructBuilder> {
         let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
         Ok(StructBuilder {
-            qualifiers: qualifiers,
-            modifiers: modifiers,
+            qualifiers,
+            modifiers,
             name: Identifier::new(name.into())?,
             struct_type: StructType::Union,
         })
@@ -326,30 +840,32 @@ macro_rules! implement_type_builder {
     pub fn anonymous_struct(self) -> StructDefinitionBuilder {
         let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
         StructDefinitionBuilder {
-            qualifiers: qualifiers,
-            modifiers: modifiers,
+            qualifiers,
+            modifiers,
             name: None,
             struct_type: StructType::Struct,
             fields: Vec::new(),
+            attributes: Attributes::default(),
         }
     }
 
     pub fn anonymous_union(self) -> StructDefinitionBuilder {
         let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
         StructDefinitionBuilder {
-            qualifiers: qualifiers,
-            modifiers: modifiers,
+            qualifiers,
+            modifiers,
             name: None,
             struct_type: StructType::Union,
             fields: Vec::new(),
+            attributes: Attributes::default(),
         }
     }
 
     pub fn enum_named<S: Into<String>>(self, name: S) -> Result<EnumBuilder> {
         let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
         Ok(EnumBuilder {
-            qualifiers: qualifiers,
-            modifiers: modifiers,
+            qualifiers,
+            modifiers,
             name: Identifier::new(name.into())?,
         })
     }
@@ -357,8 +873,8 @@ macro_rules! implement_type_builder {
     pub fn anonymous_enum(self) -> EnumDefinitionBuilder {
         let (qualifiers, modifiers) = self.get_qualifiers_modifiers();
         EnumDefinitionBuilder {
-            qualifiers: qualifiers,
-            modifiers: modifiers,
+            qualifiers,
+            modifiers,
             name: None,
             enumerators: Vec::new(),
         }
@@ -370,6 +886,12 @@ pub struct TypeBuilder {
     modifiers: Vec<TypeModifier>,
 }
 
+impl Default for TypeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TypeBuilder {
     fn get_qualifiers_modifiers(self) -> (Qualifiers, Vec<TypeModifier>) {
         (Qualifiers::none(), self.modifiers)
@@ -388,11 +910,38 @@ impl TypeBuilder {
         self
     }
 
-    fn function_returning(mut self, args: Vec<Type>) -> Self {
-        self.modifiers.push(TypeModifier::Function(args));
+    // A function taking unnamed parameters of the given types, or unspecified ones if empty
+    pub fn function_returning(mut self, args: Vec<Type>) -> Self {
+        let parameters = args
+            .into_iter()
+            .fold(ParameterList::unspecified(), |list, r#type| {
+                list.unnamed_parameter(r#type)
+            });
+        self.modifiers.push(TypeModifier::Function(parameters));
         self
     }
 
+    // A function returning an array is only caught when lowering, as `array_of` can't fail
+    pub fn function_of(mut self, parameters: ParameterList) -> Result<Self> {
+        let function = TypeModifier::Function(parameters);
+        if let Some(error) = self
+            .modifiers
+            .last()
+            .and_then(|outer| outer.nesting_error(&function))
+        {
+            return Err(error("function".to_string()));
+        }
+        self.modifiers.push(function);
+        Ok(self)
+    }
+
+    // Shorthand for `pointer_to().function_of(parameters)`, as used for callbacks
+    pub fn pointer_to_function_of(self, parameters: ParameterList) -> Self {
+        let mut builder = self.pointer_to();
+        builder.modifiers.push(TypeModifier::Function(parameters));
+        builder
+    }
+
     pub fn array_of(mut self) -> Self {
         self.modifiers.push(TypeModifier::Array(None));
         self
@@ -409,7 +958,7 @@ impl TypeBuilder {
             modifiers: self.modifiers,
             qualifiers: Qualifiers {
                 constant: true,
-                volatile: false,
+                ..Qualifiers::none()
             },
         }
     }
@@ -418,8 +967,8 @@ impl TypeBuilder {
         QualifiedTypeBuilder {
             modifiers: self.modifiers,
             qualifiers: Qualifiers {
-                constant: false,
                 volatile: true,
+                ..Qualifiers::none()
             },
         }
     }
@@ -430,6 +979,27 @@ impl TypeBuilder {
             qualifiers: Qualifiers {
                 constant: true,
                 volatile: true,
+                ..Qualifiers::none()
+            },
+        }
+    }
+
+    pub fn atomic(self) -> QualifiedTypeBuilder {
+        QualifiedTypeBuilder {
+            modifiers: self.modifiers,
+            qualifiers: Qualifiers {
+                atomic: true,
+                ..Qualifiers::none()
+            },
+        }
+    }
+
+    pub fn restrict(self) -> RestrictedPointerBuilder {
+        RestrictedPointerBuilder {
+            modifiers: self.modifiers,
+            qualifiers: Qualifiers {
+                restrict: true,
+                ..Qualifiers::none()
             },
         }
     }
@@ -452,12 +1022,69 @@ impl QualifiedTypeBuilder {
             qualifiers,
         } = self;
         modifiers.push(TypeModifier::Pointer(qualifiers));
-        TypeBuilder {
-            modifiers: modifiers,
+        TypeBuilder { modifiers }
+    }
+
+    pub fn constant(mut self) -> Self {
+        self.qualifiers.constant = true;
+        self
+    }
+
+    pub fn volatile(mut self) -> Self {
+        self.qualifiers.volatile = true;
+        self
+    }
+
+    pub fn atomic(mut self) -> Self {
+        self.qualifiers.atomic = true;
+        self
+    }
+
+    pub fn restrict(self) -> RestrictedPointerBuilder {
+        let QualifiedTypeBuilder {
+            modifiers,
+            mut qualifiers,
+        } = self;
+        qualifiers.restrict = true;
+        RestrictedPointerBuilder {
+            modifiers,
+            qualifiers,
         }
     }
 }
 
+// Qualifiers including `restrict`, which can only be applied to a pointer
+pub struct RestrictedPointerBuilder {
+    modifiers: Vec<TypeModifier>,
+    qualifiers: Qualifiers,
+}
+
+impl RestrictedPointerBuilder {
+    pub fn constant(mut self) -> Self {
+        self.qualifiers.constant = true;
+        self
+    }
+
+    pub fn volatile(mut self) -> Self {
+        self.qualifiers.volatile = true;
+        self
+    }
+
+    pub fn atomic(mut self) -> Self {
+        self.qualifiers.atomic = true;
+        self
+    }
+
+    pub fn pointer_to(self) -> TypeBuilder {
+        let RestrictedPointerBuilder {
+            mut modifiers,
+            qualifiers,
+        } = self;
+        modifiers.push(TypeModifier::Pointer(qualifiers));
+        TypeBuilder { modifiers }
+    }
+}
+
 pub struct StructBuilder {
     qualifiers: Qualifiers,
     modifiers: Vec<TypeModifier>,
@@ -466,25 +1093,37 @@ pub struct StructBuilder {
 }
 
 impl StructBuilder {
-    fn finish(self) -> Type {
+    // A reference to the struct by tag, which may be incomplete, e.g. for `struct node *next`
+    pub fn finish(self) -> Type {
         Type {
             base: BaseType::Struct {
                 name: Some(self.name),
                 struct_type: self.struct_type,
                 fields: None,
+                attributes: Attributes::default(),
             },
             qualifiers: self.qualifiers,
             modifiers: self.modifiers,
         }
     }
 
-    fn with_fields(self) -> StructDefinitionBuilder {
+    // A declaration of just the tag, such as `struct foo;`
+    pub fn forward_declaration(self) -> Result<Declaration> {
+        let r#type = self.finish();
+        if !r#type.qualifiers.is_none() {
+            return Err(Error::MissingDeclarator(r#type.to_string()));
+        }
+        Declaration::type_only(r#type)
+    }
+
+    pub fn with_fields(self) -> StructDefinitionBuilder {
         StructDefinitionBuilder {
             qualifiers: self.qualifiers,
             modifiers: self.modifiers,
             name: Some(self.name),
             struct_type: self.struct_type,
             fields: Vec::new(),
+            attributes: Attributes::default(),
         }
     }
 }
@@ -495,19 +1134,34 @@ pub struct StructDefinitionBuilder {
     name: Option<Identifier>,
     struct_type: StructType,
     fields: Vec<Field>,
+    attributes: Attributes,
 }
 
 impl StructDefinitionBuilder {
-    pub fn finish(self) -> Type {
-        Type {
+    pub fn finish(self) -> Result<Type> {
+        // A flexible array member must come last, after at least one other named member
+        let named = self
+            .fields
+            .iter()
+            .filter(|field| field.name.is_some())
+            .count();
+        for (i, field) in self.fields.iter().enumerate() {
+            let last = i == self.fields.len() - 1;
+            let valid = last && named > 1 && matches!(self.struct_type, StructType::Struct);
+            if field.is_flexible() && !valid {
+                return Err(Error::MisplacedFlexibleArray(field.to_string()));
+            }
+        }
+        Ok(Type {
             base: BaseType::Struct {
                 name: self.name,
                 struct_type: self.struct_type,
                 fields: Some(self.fields),
+                attributes: self.attributes,
             },
             qualifiers: self.qualifiers,
             modifiers: self.modifiers,
-        }
+        })
     }
 
     pub fn named_field<S: Into<String>>(mut self, r#type: Type, name: S) -> Result<Self> {
@@ -515,6 +1169,7 @@ impl StructDefinitionBuilder {
             r#type: Box::new(r#type),
             name: Some(Identifier::new(name.into())?),
             width: None,
+            attributes: Attributes::default(),
         });
         Ok(self)
     }
@@ -524,6 +1179,7 @@ impl StructDefinitionBuilder {
             r#type: Box::new(r#type),
             name: None,
             width: None,
+            attributes: Attributes::default(),
         });
         self
     }
@@ -538,15 +1194,32 @@ impl StructDefinitionBuilder {
             r#type: Box::new(r#type),
             name: Some(Identifier::new(name.into())?),
             width: Some(Box::new(width)),
+            attributes: Attributes::default(),
         });
         Ok(self)
     }
 
+    // Applies to the struct or union itself, such as packing a wire format
+    pub fn attribute(mut self, attribute: Attribute) -> Result<Self> {
+        self.attributes.push(attribute, Subject::Type)?;
+        Ok(self)
+    }
+
+    // Applies to the most recently added member
+    pub fn field_attribute(mut self, attribute: Attribute) -> Result<Self> {
+        match self.fields.last_mut() {
+            Some(field) => field.attributes.push(attribute, field.subject())?,
+            None => return Err(Error::MisplacedAttribute(attribute.to_string())),
+        }
+        Ok(self)
+    }
+
     pub fn anonymous_bit_field(mut self, r#type: Type, width: Expression) -> Self {
         self.fields.push(Field {
             r#type: Box::new(r#type),
             name: None,
             width: Some(Box::new(width)),
+            attributes: Attributes::default(),
         });
         self
     }
@@ -676,7 +1349,7 @@ mod tests {
 
     #[test]
     fn empty_anon_struct() {
-        let t = TypeBuilder::new().anonymous_struct().finish();
+        let t = TypeBuilder::new().anonymous_struct().finish().unwrap();
         assert_eq!(
             t.render(Some(&Identifier::new("foo").unwrap())),
             "struct { } foo"
@@ -685,7 +1358,7 @@ mod tests {
 
     #[test]
     fn empty_anon_union() {
-        let t = TypeBuilder::new().anonymous_union().finish();
+        let t = TypeBuilder::new().anonymous_union().finish().unwrap();
         assert_eq!(
             t.render(Some(&Identifier::new("foo").unwrap())),
             "union { } foo"
@@ -711,7 +1384,8 @@ mod tests {
             .struct_named("foo")
             .unwrap()
             .with_fields()
-            .finish();
+            .finish()
+            .unwrap();
         assert_eq!(
             t.render(Some(&Identifier::new("bar").unwrap())),
             "struct foo { } bar"
@@ -724,7 +1398,8 @@ mod tests {
             .union_named("foo")
             .unwrap()
             .with_fields()
-            .finish();
+            .finish()
+            .unwrap();
         assert_eq!(
             t.render(Some(&Identifier::new("bar").unwrap())),
             "union foo { } bar"
@@ -736,18 +1411,19 @@ mod tests {
         let t = TypeBuilder::new()
             .anonymous_struct()
             .anonymous_field(TypeBuilder::new().int())
-            .named_field(TypeBuilder::new().array_of().pointer_to().void(), "foo")
-            .unwrap()
             .named_bit_field(
                 TypeBuilder::new().char(),
                 "bar",
                 Expression::Literal(crate::Literal::Signed(1)),
             )
             .unwrap()
-            .finish();
+            .named_field(TypeBuilder::new().array_of().pointer_to().void(), "foo")
+            .unwrap()
+            .finish()
+            .unwrap();
         assert_eq!(
             format!("{}", t),
-            "struct { int; void * foo[]; char bar : 1; }"
+            "struct { int; char bar : 1; void * foo[]; }"
         );
     }
 
@@ -762,4 +1438,364 @@ mod tests {
             .finish();
         assert_eq!(format!("{}", t), "enum { FOO, BAR = 1 }");
     }
+
+    #[test]
+    fn function_pointers() {
+        let callback = TypeBuilder::new()
+            .pointer_to_function_of(
+                ParameterList::new()
+                    .parameter(TypeBuilder::new().pointer_to().void(), "ctx")
+                    .unwrap()
+                    .parameter(TypeBuilder::new().pointer_to().constant().char(), "msg")
+                    .unwrap(),
+            )
+            .int();
+        assert_eq!(
+            callback.render(Some(&Identifier::new("callback_t").unwrap())),
+            "int (* callback_t)(void * ctx, const char * msg)"
+        );
+
+        let t = TypeBuilder::new()
+            .pointer_to_function_of(ParameterList::new())
+            .void();
+        assert_eq!(format!("{}", t), "void (*)(void)");
+        let t = TypeBuilder::new()
+            .pointer_to_function_of(ParameterList::unspecified())
+            .void();
+        assert_eq!(format!("{}", t), "void (*)()");
+
+        // A function returning a pointer to a variadic function
+        let t = TypeBuilder::new()
+            .function_of(ParameterList::new().unnamed_parameter(TypeBuilder::new().int()))
+            .unwrap()
+            .pointer_to()
+            .function_of(
+                ParameterList::new()
+                    .parameter(TypeBuilder::new().pointer_to().constant().char(), "fmt")
+                    .unwrap()
+                    .variadic(),
+            )
+            .unwrap()
+            .int();
+        assert_eq!(
+            t.render(Some(&Identifier::new("lookup").unwrap())),
+            "int (* lookup(int))(const char * fmt, ...)"
+        );
+    }
+
+    #[test]
+    fn invalid_derivations() {
+        assert!(matches!(
+            TypeBuilder::new()
+                .function_of(ParameterList::new())
+                .unwrap()
+                .function_of(ParameterList::new()),
+            Err(Error::InvalidReturnType(_))
+        ));
+        assert!(matches!(
+            TypeBuilder::new()
+                .array_of()
+                .function_of(ParameterList::new()),
+            Err(Error::InvalidElementType(_))
+        ));
+        let mut t = TypeBuilder::new()
+            .function_of(ParameterList::new())
+            .unwrap()
+            .array_of()
+            .int();
+        assert!(matches!(
+            t.lower(Standard::C23, Dialect::Gnu),
+            Err(Error::InvalidReturnType(_))
+        ));
+        TypeBuilder::new()
+            .array_of()
+            .pointer_to_function_of(ParameterList::new())
+            .int()
+            .lower(Standard::C23, Dialect::Gnu)
+            .unwrap();
+    }
+
+    #[test]
+    fn parameter_lists() {
+        ParameterList::new()
+            .parameter(TypeBuilder::new().int(), "a")
+            .unwrap()
+            .parameter(TypeBuilder::new().int(), "a")
+            .unwrap_err();
+        let mut only_variadic = ParameterList::new().variadic();
+        assert_eq!(format!("{}", only_variadic), "(...)");
+        only_variadic
+            .lower(Standard::C17, Dialect::Gnu)
+            .unwrap_err();
+        only_variadic.lower(Standard::C23, Dialect::Gnu).unwrap();
+    }
+
+    #[test]
+    fn extended_base_types() {
+        assert_eq!(
+            format!("{}", TypeBuilder::new().double_complex()),
+            "double _Complex"
+        );
+        assert_eq!(
+            format!("{}", TypeBuilder::new().constant().unsigned()),
+            "const unsigned"
+        );
+        assert_eq!(
+            format!("{}", TypeBuilder::new().unsigned_bit_int(1).unwrap()),
+            "unsigned _BitInt(1)"
+        );
+        assert_eq!(
+            format!("{}", TypeBuilder::new().bit_int(24).unwrap()),
+            "_BitInt(24)"
+        );
+        assert!(matches!(
+            TypeBuilder::new().bit_int(1),
+            Err(Error::InvalidBitIntWidth(1))
+        ));
+        let mut t = TypeBuilder::new().bit_int(24).unwrap();
+        t.lower(Standard::Gnu17, Dialect::Gnu).unwrap_err();
+        t.lower(Standard::C23, Dialect::Gnu).unwrap();
+    }
+
+    #[test]
+    fn required_includes() {
+        let t = TypeBuilder::new().pointer_to().size_t();
+        assert_eq!(format!("{}", t), "size_t *");
+        let includes = |t: &Type| {
+            t.required_includes()
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(includes(&t), ["#include <stddef.h>"]);
+
+        let t = TypeBuilder::new()
+            .anonymous_struct()
+            .named_field(TypeBuilder::new().uint32_t(), "a")
+            .unwrap()
+            .named_field(TypeBuilder::new().bool(), "b")
+            .unwrap()
+            .named_field(
+                TypeBuilder::new()
+                    .pointer_to_function_of(
+                        ParameterList::new().unnamed_parameter(TypeBuilder::new().int64_t()),
+                    )
+                    .void(),
+                "c",
+            )
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(
+            includes(&t),
+            ["#include <stdint.h>", "#include <stdbool.h>"]
+        );
+        assert!(TypeBuilder::new().int().required_includes().is_empty());
+    }
+
+    #[test]
+    fn library_type_standards() {
+        let mut t = TypeBuilder::new().uint32_t();
+        t.lower(Standard::C89, Dialect::Gnu).unwrap_err();
+        t.lower(Standard::Gnu89, Dialect::Gnu).unwrap();
+        t.lower(Standard::C99, Dialect::Gnu).unwrap();
+        let mut t = TypeBuilder::new().max_align_t();
+        t.lower(Standard::Gnu99, Dialect::Gnu).unwrap_err();
+        t.lower(Standard::C11, Dialect::Gnu).unwrap();
+        TypeBuilder::new()
+            .size_t()
+            .lower(Standard::C89, Dialect::Gnu)
+            .unwrap();
+    }
+
+    #[test]
+    fn restrict_and_atomic() {
+        let t = TypeBuilder::new()
+            .restrict()
+            .pointer_to()
+            .constant()
+            .float();
+        assert_eq!(
+            t.render(Some(&Identifier::new("src").unwrap())),
+            "const float * restrict src"
+        );
+        let t = TypeBuilder::new()
+            .constant()
+            .restrict()
+            .volatile()
+            .pointer_to()
+            .atomic()
+            .int();
+        assert_eq!(
+            t.render(Some(&Identifier::new("p").unwrap())),
+            "_Atomic int * const volatile restrict p"
+        );
+
+        let t = TypeBuilder::new()
+            .atomic_of(
+                TypeBuilder::new()
+                    .pointer_to()
+                    .alias_named("node_t")
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            t.render(Some(&Identifier::new("head").unwrap())),
+            "_Atomic(node_t *) head"
+        );
+        let mut t = TypeBuilder::new()
+            .constant()
+            .atomic_of(TypeBuilder::new().long())
+            .unwrap();
+        assert_eq!(format!("{}", t), "const _Atomic(long)");
+        t.lower(Standard::C99, Dialect::Gnu).unwrap_err();
+        t.lower(Standard::C11, Dialect::Gnu).unwrap();
+
+        TypeBuilder::new()
+            .atomic_of(TypeBuilder::new().constant().int())
+            .unwrap_err();
+        TypeBuilder::new()
+            .atomic_of(TypeBuilder::new().array_of().int())
+            .unwrap_err();
+        let qualified_pointer = TypeBuilder::new().constant().pointer_to().char();
+        assert_eq!(format!("{}", qualified_pointer), "char * const");
+        TypeBuilder::new().atomic_of(qualified_pointer).unwrap_err();
+        let pointer_to_const = TypeBuilder::new().pointer_to().constant().int();
+        let t = TypeBuilder::new().atomic_of(pointer_to_const).unwrap();
+        assert_eq!(format!("{}", t), "_Atomic(const int *)");
+        let inner = TypeBuilder::new()
+            .atomic_of(TypeBuilder::new().int())
+            .unwrap();
+        assert!(matches!(
+            TypeBuilder::new().atomic_of(inner),
+            Err(Error::InvalidAtomicType(_))
+        ));
+
+        let mut t = TypeBuilder::new().restrict().pointer_to().char();
+        t.lower(Standard::Gnu89, Dialect::Gnu).unwrap_err();
+        t.lower(Standard::C99, Dialect::Gnu).unwrap();
+    }
+
+    #[test]
+    fn linked_structs() {
+        let node = TypeBuilder::new()
+            .struct_named("node")
+            .unwrap()
+            .with_fields()
+            .named_field(
+                TypeBuilder::new()
+                    .pointer_to()
+                    .struct_named("node")
+                    .unwrap()
+                    .finish(),
+                "next",
+            )
+            .unwrap()
+            .named_field(TypeBuilder::new().int(), "value")
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(
+            format!("{}", node),
+            "struct node { struct node * next; int value; }"
+        );
+        let forward = TypeBuilder::new()
+            .union_named("value")
+            .unwrap()
+            .forward_declaration()
+            .unwrap();
+        assert_eq!(format!("{}", forward), "union value;");
+        TypeBuilder::new()
+            .pointer_to()
+            .struct_named("node")
+            .unwrap()
+            .forward_declaration()
+            .unwrap_err();
+        assert!(matches!(
+            TypeBuilder::new()
+                .constant()
+                .struct_named("node")
+                .unwrap()
+                .forward_declaration(),
+            Err(Error::MissingDeclarator(_))
+        ));
+    }
+
+    #[test]
+    fn calling_conventions() {
+        let mut t = TypeBuilder::new()
+            .pointer_to_function_of(
+                ParameterList::new()
+                    .parameter(TypeBuilder::new().pointer_to().void(), "ctx")
+                    .unwrap()
+                    .calling_convention(CallingConvention::Stdcall),
+            )
+            .int();
+        let name = Identifier::new("handler").unwrap();
+        assert_eq!(
+            t.render(Some(&name)),
+            "int (__attribute__((stdcall)) * handler)(void * ctx)"
+        );
+        t.lower(Standard::C99, Dialect::Msvc).unwrap();
+        assert_eq!(
+            t.render(Some(&name)),
+            "int (__stdcall * handler)(void * ctx)"
+        );
+        t.lower(Standard::C23, Dialect::Standard).unwrap_err();
+    }
+
+    #[test]
+    fn flexible_array_members() {
+        let packet = || {
+            TypeBuilder::new()
+                .struct_named("packet")
+                .unwrap()
+                .with_fields()
+                .named_field(TypeBuilder::new().size_t(), "length")
+                .unwrap()
+        };
+        let data = || TypeBuilder::new().array_of().unsigned_char();
+        let mut t = packet()
+            .named_field(data(), "data")
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(
+            format!("{}", t),
+            "struct packet { size_t length; unsigned char data[]; }"
+        );
+        t.lower(Standard::C89, Dialect::Gnu).unwrap_err();
+        t.lower(Standard::Gnu89, Dialect::Gnu).unwrap();
+        t.lower(Standard::C99, Dialect::Gnu).unwrap();
+
+        let misplaced = packet()
+            .named_field(data(), "data")
+            .unwrap()
+            .named_field(TypeBuilder::new().int(), "crc")
+            .unwrap()
+            .finish();
+        assert!(matches!(misplaced, Err(Error::MisplacedFlexibleArray(_))));
+        TypeBuilder::new()
+            .anonymous_struct()
+            .named_field(data(), "data")
+            .unwrap()
+            .finish()
+            .unwrap_err();
+        TypeBuilder::new()
+            .anonymous_union()
+            .named_field(TypeBuilder::new().int(), "a")
+            .unwrap()
+            .named_field(data(), "data")
+            .unwrap()
+            .finish()
+            .unwrap_err();
+        // A pointer to an array is not a flexible array member
+        packet()
+            .named_field(TypeBuilder::new().pointer_to().array_of().int(), "rows")
+            .unwrap()
+            .named_field(TypeBuilder::new().int(), "count")
+            .unwrap()
+            .finish()
+            .unwrap();
+    }
 }